@@ -0,0 +1,43 @@
+use base64::{engine::general_purpose::STANDARD as base64, Engine};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use headers::Header;
+use headers_content_md5::ContentMd5;
+use http::HeaderValue;
+
+/// The encoding used before `ContentMd5::to_header_value`, for comparison.
+fn encode_via_string(md5: &ContentMd5, values: &mut Vec<HeaderValue>) {
+    let encoded = base64.encode(md5.0);
+    if let Ok(value) = HeaderValue::from_str(&encoded) {
+        values.push(value);
+    }
+}
+
+fn encode(c: &mut Criterion) {
+    let md5 = ContentMd5(*b"Check Integrity!");
+    let mut group = c.benchmark_group("encode");
+
+    group.bench_function("string", |b| {
+        let mut values = Vec::with_capacity(1);
+        b.iter(|| {
+            values.clear();
+            encode_via_string(black_box(&md5), &mut values);
+        })
+    });
+
+    group.bench_function("stack_buffer", |b| {
+        let mut values = Vec::with_capacity(1);
+        b.iter(|| {
+            values.clear();
+            black_box(&md5).encode(&mut values);
+        })
+    });
+
+    group.bench_function("to_base64_array", |b| {
+        b.iter(|| black_box(&md5).to_base64_array())
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, encode);
+criterion_main!(benches);
@@ -0,0 +1,141 @@
+//! Digest algorithms for [`DigestHeader`](crate::DigestHeader).
+//!
+//! The marker types in this module select the algorithm of a [`DigestHeader`](crate::DigestHeader).
+//! Decoding and encoding work for every algorithm; computing digests requires the crate
+//! feature named on the respective [`Compute`] implementation.
+
+use http::header::HeaderName;
+use std::fmt::Debug;
+use std::hash::Hash;
+
+/// The longest digest supported by [`DigestHeader`](crate::DigestHeader), in bytes.
+pub const MAX_DIGEST_LEN: usize = 64;
+
+/// A digest algorithm carried by a [`DigestHeader`](crate::DigestHeader).
+///
+/// Implementors are zero-sized marker types.
+pub trait Algorithm: Copy + Debug + Default + Eq + Ord + Hash + Send + Sync + 'static {
+    /// The raw digest, usually a byte array of [`Algorithm::DIGEST_LEN`] bytes.
+    type Digest: Copy
+        + Debug
+        + Eq
+        + Ord
+        + Hash
+        + Send
+        + Sync
+        + AsRef<[u8]>
+        + for<'a> TryFrom<&'a [u8]>;
+
+    /// The length of the digest in bytes, at most [`MAX_DIGEST_LEN`].
+    const DIGEST_LEN: usize;
+
+    /// The algorithm token used by the `Content-Digest` and `Digest` fields, e.g. `sha-256`.
+    const NAME: &'static str;
+
+    /// The name of the header carrying a single digest of this algorithm.
+    fn header_name() -> &'static HeaderName;
+}
+
+/// An [`Algorithm`] whose digests can be computed by this crate.
+pub trait Compute: Algorithm {
+    /// The incremental hashing state.
+    type Hasher: Clone + Debug + Default + Send + Sync;
+
+    /// Feeds `data` into the hashing state.
+    fn update(hasher: &mut Self::Hasher, data: &[u8]);
+
+    /// Consumes the hashing state and returns the digest.
+    fn finalize(hasher: Self::Hasher) -> Self::Digest;
+}
+
+/// The MD5 algorithm, carried by the `Content-MD5` header.
+///
+/// Computing MD5 digests requires the `md5` feature.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Md5;
+
+/// The SHA-1 algorithm, carried by the non-standard `Content-SHA1` header.
+///
+/// Computing SHA-1 digests requires the `sha1` feature.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Sha1;
+
+/// The SHA-256 algorithm, carried by the non-standard `Content-SHA256` header.
+///
+/// Computing SHA-256 digests requires the `sha2` feature.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Sha256;
+
+/// The SHA-512 algorithm, carried by the non-standard `Content-SHA512` header.
+///
+/// Computing SHA-512 digests requires the `sha2` feature.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Sha512;
+
+static CONTENT_MD5: HeaderName = HeaderName::from_static("content-md5");
+static CONTENT_SHA1: HeaderName = HeaderName::from_static("content-sha1");
+static CONTENT_SHA256: HeaderName = HeaderName::from_static("content-sha256");
+static CONTENT_SHA512: HeaderName = HeaderName::from_static("content-sha512");
+
+impl Algorithm for Md5 {
+    type Digest = [u8; 16];
+    const DIGEST_LEN: usize = 16;
+    const NAME: &'static str = "md5";
+
+    fn header_name() -> &'static HeaderName {
+        &CONTENT_MD5
+    }
+}
+
+impl Algorithm for Sha1 {
+    type Digest = [u8; 20];
+    const DIGEST_LEN: usize = 20;
+    const NAME: &'static str = "sha";
+
+    fn header_name() -> &'static HeaderName {
+        &CONTENT_SHA1
+    }
+}
+
+impl Algorithm for Sha256 {
+    type Digest = [u8; 32];
+    const DIGEST_LEN: usize = 32;
+    const NAME: &'static str = "sha-256";
+
+    fn header_name() -> &'static HeaderName {
+        &CONTENT_SHA256
+    }
+}
+
+impl Algorithm for Sha512 {
+    type Digest = [u8; 64];
+    const DIGEST_LEN: usize = 64;
+    const NAME: &'static str = "sha-512";
+
+    fn header_name() -> &'static HeaderName {
+        &CONTENT_SHA512
+    }
+}
+
+/// Implements [`Compute`] through a RustCrypto hasher.
+macro_rules! impl_compute {
+    ($algorithm:ty, $feature:literal, $krate:ident::$hasher:ident) => {
+        #[cfg(feature = $feature)]
+        impl Compute for $algorithm {
+            type Hasher = $krate::$hasher;
+
+            fn update(hasher: &mut Self::Hasher, data: &[u8]) {
+                $krate::Digest::update(hasher, data);
+            }
+
+            fn finalize(hasher: Self::Hasher) -> Self::Digest {
+                $krate::Digest::finalize(hasher).into()
+            }
+        }
+    };
+}
+
+impl_compute!(Md5, "md5", md5::Md5);
+impl_compute!(Sha1, "sha1", sha1::Sha1);
+impl_compute!(Sha256, "sha2", sha2::Sha256);
+impl_compute!(Sha512, "sha2", sha2::Sha512);
@@ -0,0 +1,9 @@
+//! [`http_body::Body`] adapters, available with the `body` feature.
+
+mod compute;
+mod trailer;
+mod verify;
+
+pub use compute::{ComputeContentMd5, ComputeDigestBody};
+pub use trailer::{VerifyContentMd5Trailer, VerifyDigestTrailer};
+pub use verify::{VerifyBodyError, VerifyContentMd5, VerifyDigestBody};
@@ -0,0 +1,169 @@
+use crate::algorithm::{Compute, Md5};
+use crate::DigestHasher;
+use headers::HeaderMapExt;
+use http::HeaderMap;
+use http_body::{Body, Frame, SizeHint};
+use pin_project_lite::pin_project;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+
+pin_project! {
+    /// A [`Body`] that computes the [`DigestHeader`](crate::DigestHeader) of the wrapped body
+    /// while it is being polled and emits it as a trailer, e.g. `content-md5`.
+    ///
+    /// Data frames are passed through unchanged. If the wrapped body sends trailers of its own,
+    /// the digest is added to them; otherwise a trailers frame is appended at the end of the
+    /// stream.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// use bytes::Bytes;
+    /// use headers::HeaderMapExt;
+    /// use http_body_util::{BodyExt, Full};
+    /// use headers_content_md5::{ComputeContentMd5, ContentMd5};
+    ///
+    /// let body = ComputeContentMd5::new(Full::new(Bytes::from("Check Integrity!")));
+    ///
+    /// let collected = body.collect().await.unwrap();
+    /// let trailers = collected.trailers().unwrap();
+    /// assert_eq!(
+    ///     trailers.typed_get::<ContentMd5>(),
+    ///     Some(ContentMd5::compute(b"Check Integrity!"))
+    /// );
+    /// # }
+    /// ```
+    #[derive(Debug)]
+    pub struct ComputeDigestBody<B, A: Compute> {
+        #[pin]
+        inner: B,
+        hasher: Option<DigestHasher<A>>,
+    }
+}
+
+/// A [`ComputeDigestBody`] emitting a [`ContentMd5`](type@crate::ContentMd5) trailer.
+pub type ComputeContentMd5<B> = ComputeDigestBody<B, Md5>;
+
+impl<B, A: Compute> ComputeDigestBody<B, A> {
+    /// Wraps `inner`, computing the digest of its data.
+    pub fn new(inner: B) -> Self {
+        Self {
+            inner,
+            hasher: Some(DigestHasher::new()),
+        }
+    }
+
+    /// Returns a reference to the wrapped body.
+    pub fn get_ref(&self) -> &B {
+        &self.inner
+    }
+
+    /// Consumes the adapter and returns the wrapped body.
+    pub fn into_inner(self) -> B {
+        self.inner
+    }
+}
+
+impl<B, A> Body for ComputeDigestBody<B, A>
+where
+    B: Body,
+    B::Data: AsRef<[u8]>,
+    A: Compute,
+{
+    type Data = B::Data;
+    type Error = B::Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = self.project();
+        let Some(hasher) = this.hasher.as_mut() else {
+            return Poll::Ready(None);
+        };
+
+        match ready!(this.inner.poll_frame(cx)) {
+            Some(Ok(frame)) => match frame.into_trailers() {
+                Ok(mut trailers) => {
+                    trailers.typed_insert(std::mem::take(hasher).finalize());
+                    *this.hasher = None;
+                    Poll::Ready(Some(Ok(Frame::trailers(trailers))))
+                }
+                Err(frame) => {
+                    if let Some(data) = frame.data_ref() {
+                        hasher.update(data);
+                    }
+                    Poll::Ready(Some(Ok(frame)))
+                }
+            },
+            Some(Err(err)) => Poll::Ready(Some(Err(err))),
+            None => {
+                let mut trailers = HeaderMap::new();
+                trailers.typed_insert(std::mem::take(hasher).finalize());
+                *this.hasher = None;
+                Poll::Ready(Some(Ok(Frame::trailers(trailers))))
+            }
+        }
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.hasher.is_none()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ComputeContentMd5, ContentMd5};
+    use bytes::Bytes;
+    use headers::HeaderMapExt;
+    use http::{HeaderMap, HeaderValue};
+    use http_body::Frame;
+    use http_body_util::{BodyExt, StreamBody};
+    use std::convert::Infallible;
+
+    #[tokio::test]
+    async fn appends_trailer() {
+        let frames = ["Check ", "Integrity", "!"]
+            .map(|chunk| Ok::<_, Infallible>(Frame::data(Bytes::from_static(chunk.as_bytes()))));
+        let body = ComputeContentMd5::new(StreamBody::new(futures_util::stream::iter(frames)));
+
+        let collected = body.collect().await.unwrap();
+        assert_eq!(
+            collected.trailers().unwrap().typed_get::<ContentMd5>(),
+            Some(ContentMd5::compute(b"Check Integrity!"))
+        );
+        assert_eq!(collected.to_bytes(), "Check Integrity!");
+    }
+
+    #[tokio::test]
+    async fn extends_existing_trailers() {
+        let mut trailers = HeaderMap::new();
+        trailers.insert("x-checksum-source", HeaderValue::from_static("origin"));
+        let frames = [
+            Ok::<_, Infallible>(Frame::data(Bytes::from_static(b"Check Integrity!"))),
+            Ok(Frame::trailers(trailers)),
+        ];
+        let mut body = ComputeContentMd5::new(StreamBody::new(futures_util::stream::iter(frames)));
+
+        assert!(body.frame().await.unwrap().unwrap().is_data());
+        let trailers = body
+            .frame()
+            .await
+            .unwrap()
+            .unwrap()
+            .into_trailers()
+            .unwrap();
+        assert_eq!(trailers["x-checksum-source"], "origin");
+        assert_eq!(
+            trailers.typed_get::<ContentMd5>(),
+            Some(ContentMd5::compute(b"Check Integrity!"))
+        );
+        assert!(body.frame().await.is_none());
+    }
+}
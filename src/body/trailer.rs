@@ -0,0 +1,180 @@
+use crate::algorithm::{Compute, Md5};
+use crate::body::VerifyBodyError;
+use crate::{DigestError, DigestHasher, DigestHeader, DigestMismatch};
+use http_body::{Body, Frame, SizeHint};
+use pin_project_lite::pin_project;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+
+pin_project! {
+    /// A [`Body`] that verifies the data frames of the wrapped body against a
+    /// [`DigestHeader`] sent as a trailer, e.g. after a `Trailer: Content-MD5` announcement.
+    ///
+    /// Frames are passed through unchanged. When the trailers frame arrives, the digest of all
+    /// preceding data frames is compared against the one it carries; on a mismatch a terminal
+    /// [`VerifyBodyError::Mismatch`] error is yielded instead of the trailers. If the body ends
+    /// without the trailer, [`VerifyBodyError::MissingTrailer`] is yielded instead of the end of
+    /// the stream.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// use bytes::Bytes;
+    /// use http_body_util::{BodyExt, Full};
+    /// use headers_content_md5::{ComputeContentMd5, VerifyContentMd5Trailer};
+    ///
+    /// // The sender emits the digest as a `content-md5` trailer.
+    /// let body = ComputeContentMd5::new(Full::new(Bytes::from("Check Integrity!")));
+    /// let body = VerifyContentMd5Trailer::new(body);
+    ///
+    /// let bytes = body.collect().await.unwrap().to_bytes();
+    /// assert_eq!(bytes, "Check Integrity!");
+    /// # }
+    /// ```
+    #[derive(Debug)]
+    pub struct VerifyDigestTrailer<B, A: Compute> {
+        #[pin]
+        inner: B,
+        hasher: Option<DigestHasher<A>>,
+    }
+}
+
+/// A [`VerifyDigestTrailer`] verifying a [`ContentMd5`](type@crate::ContentMd5) trailer.
+pub type VerifyContentMd5Trailer<B> = VerifyDigestTrailer<B, Md5>;
+
+impl<B, A: Compute> VerifyDigestTrailer<B, A> {
+    /// Wraps `inner`, expecting its data to hash to the digest in its trailers.
+    pub fn new(inner: B) -> Self {
+        Self {
+            inner,
+            hasher: Some(DigestHasher::new()),
+        }
+    }
+
+    /// Returns a reference to the wrapped body.
+    pub fn get_ref(&self) -> &B {
+        &self.inner
+    }
+
+    /// Consumes the adapter and returns the wrapped body.
+    pub fn into_inner(self) -> B {
+        self.inner
+    }
+}
+
+impl<B, A> Body for VerifyDigestTrailer<B, A>
+where
+    B: Body,
+    B::Data: AsRef<[u8]>,
+    A: Compute,
+{
+    type Data = B::Data;
+    type Error = VerifyBodyError<B::Error, A>;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = self.project();
+        let Some(hasher) = this.hasher.as_mut() else {
+            return Poll::Ready(None);
+        };
+
+        match ready!(this.inner.poll_frame(cx)) {
+            Some(Ok(frame)) => {
+                if let Some(data) = frame.data_ref() {
+                    hasher.update(data);
+                    return Poll::Ready(Some(Ok(frame)));
+                }
+                let Some(trailers) = frame.trailers_ref() else {
+                    return Poll::Ready(Some(Ok(frame)));
+                };
+
+                let actual = std::mem::take(hasher).finalize();
+                *this.hasher = None;
+                let result = DigestHeader::from_trailers(trailers)
+                    .map_err(|err| match err {
+                        DigestError::Missing => VerifyBodyError::MissingTrailer,
+                        err => VerifyBodyError::InvalidTrailer(err),
+                    })
+                    .and_then(|expected| {
+                        DigestMismatch::check(expected, actual).map_err(VerifyBodyError::Mismatch)
+                    });
+                Poll::Ready(Some(result.map(|()| frame)))
+            }
+            Some(Err(err)) => Poll::Ready(Some(Err(VerifyBodyError::Body(err)))),
+            None => {
+                *this.hasher = None;
+                Poll::Ready(Some(Err(VerifyBodyError::MissingTrailer)))
+            }
+        }
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.hasher.is_none()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ContentMd5, VerifyBodyError, VerifyContentMd5Trailer};
+    use bytes::Bytes;
+    use headers::HeaderMapExt;
+    use http::HeaderMap;
+    use http_body::Frame;
+    use http_body_util::{BodyExt, StreamBody};
+    use std::convert::Infallible;
+
+    fn with_trailer(
+        chunks: &[&'static str],
+        digest: Option<ContentMd5>,
+    ) -> StreamBody<impl futures_util::Stream<Item = Result<Frame<Bytes>, Infallible>>> {
+        let mut frames = chunks
+            .iter()
+            .map(|chunk| Ok(Frame::data(Bytes::from_static(chunk.as_bytes()))))
+            .collect::<Vec<_>>();
+        if let Some(digest) = digest {
+            let mut trailers = HeaderMap::new();
+            trailers.typed_insert(digest);
+            frames.push(Ok(Frame::trailers(trailers)));
+        }
+        StreamBody::new(futures_util::stream::iter(frames))
+    }
+
+    #[tokio::test]
+    async fn passes_matching_trailer() {
+        let expected = ContentMd5::compute(b"Check Integrity!");
+        let body =
+            VerifyContentMd5Trailer::new(with_trailer(&["Check ", "Integrity!"], Some(expected)));
+
+        let collected = body.collect().await.unwrap();
+        assert_eq!(collected.trailers().unwrap().typed_get(), Some(expected));
+        assert_eq!(collected.to_bytes(), "Check Integrity!");
+    }
+
+    #[tokio::test]
+    async fn fails_on_mismatch_or_missing_trailer() {
+        let expected = ContentMd5::compute(b"Check Integrity!");
+        let mut body = VerifyContentMd5Trailer::new(with_trailer(&["Corruption!"], Some(expected)));
+        assert!(body.frame().await.unwrap().is_ok());
+        assert!(matches!(
+            body.frame().await,
+            Some(Err(VerifyBodyError::Mismatch(_)))
+        ));
+        assert!(body.frame().await.is_none());
+
+        let mut body = VerifyContentMd5Trailer::new(with_trailer(&["Check Integrity!"], None));
+        assert!(body.frame().await.unwrap().is_ok());
+        assert!(matches!(
+            body.frame().await,
+            Some(Err(VerifyBodyError::MissingTrailer))
+        ));
+        assert!(body.frame().await.is_none());
+    }
+}
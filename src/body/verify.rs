@@ -0,0 +1,199 @@
+use crate::algorithm::{Algorithm, Compute, Md5};
+use crate::{DigestError, DigestHasher, DigestHeader, DigestMismatch};
+use http_body::{Body, Frame, SizeHint};
+use pin_project_lite::pin_project;
+use std::fmt;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+
+pin_project! {
+    /// A [`Body`] that verifies the data frames of the wrapped body against an expected
+    /// [`DigestHeader`] while they are being polled.
+    ///
+    /// Frames are passed through unchanged. Once the wrapped body ends, the digest of all
+    /// data frames is compared against the expected one; on a mismatch a terminal
+    /// [`VerifyBodyError::Mismatch`] error is yielded instead of the end of the stream.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// use bytes::Bytes;
+    /// use http_body_util::{BodyExt, Full};
+    /// use headers_content_md5::{ContentMd5, VerifyContentMd5};
+    ///
+    /// let expected = ContentMd5::compute(b"Check Integrity!");
+    /// let body = VerifyContentMd5::new(Full::new(Bytes::from("Check Integrity!")), expected);
+    ///
+    /// let bytes = body.collect().await.unwrap().to_bytes();
+    /// assert_eq!(bytes, "Check Integrity!");
+    /// # }
+    /// ```
+    #[derive(Debug)]
+    pub struct VerifyDigestBody<B, A: Compute> {
+        #[pin]
+        inner: B,
+        expected: DigestHeader<A>,
+        hasher: Option<DigestHasher<A>>,
+    }
+}
+
+/// A [`VerifyDigestBody`] verifying a [`ContentMd5`](type@crate::ContentMd5).
+pub type VerifyContentMd5<B> = VerifyDigestBody<B, Md5>;
+
+impl<B, A: Compute> VerifyDigestBody<B, A> {
+    /// Wraps `inner`, expecting its data to hash to `expected`.
+    pub fn new(inner: B, expected: DigestHeader<A>) -> Self {
+        Self {
+            inner,
+            expected,
+            hasher: Some(DigestHasher::new()),
+        }
+    }
+
+    /// Returns the expected digest.
+    pub fn expected(&self) -> DigestHeader<A> {
+        self.expected
+    }
+
+    /// Returns a reference to the wrapped body.
+    pub fn get_ref(&self) -> &B {
+        &self.inner
+    }
+
+    /// Consumes the adapter and returns the wrapped body.
+    pub fn into_inner(self) -> B {
+        self.inner
+    }
+}
+
+impl<B, A> Body for VerifyDigestBody<B, A>
+where
+    B: Body,
+    B::Data: AsRef<[u8]>,
+    A: Compute,
+{
+    type Data = B::Data;
+    type Error = VerifyBodyError<B::Error, A>;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = self.project();
+        let Some(hasher) = this.hasher.as_mut() else {
+            return Poll::Ready(None);
+        };
+
+        match ready!(this.inner.poll_frame(cx)) {
+            Some(Ok(frame)) => {
+                if let Some(data) = frame.data_ref() {
+                    hasher.update(data);
+                }
+                Poll::Ready(Some(Ok(frame)))
+            }
+            Some(Err(err)) => Poll::Ready(Some(Err(VerifyBodyError::Body(err)))),
+            None => {
+                let actual = std::mem::take(hasher).finalize();
+                *this.hasher = None;
+                match DigestMismatch::check(*this.expected, actual) {
+                    Ok(()) => Poll::Ready(None),
+                    Err(mismatch) => Poll::Ready(Some(Err(VerifyBodyError::Mismatch(mismatch)))),
+                }
+            }
+        }
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.hasher.is_none()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+/// The error yielded by [`VerifyDigestBody`] and
+/// [`VerifyDigestTrailer`](crate::VerifyDigestTrailer).
+#[derive(Debug)]
+pub enum VerifyBodyError<E, A: Algorithm = Md5> {
+    /// The wrapped body failed.
+    Body(E),
+    /// The body did not match the expected digest.
+    Mismatch(DigestMismatch<A>),
+    /// The body ended without the digest trailer.
+    MissingTrailer,
+    /// The digest trailer could not be decoded.
+    InvalidTrailer(DigestError),
+}
+
+impl<E: fmt::Display, A: Algorithm> fmt::Display for VerifyBodyError<E, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Body(err) => err.fmt(f),
+            Self::Mismatch(mismatch) => mismatch.fmt(f),
+            Self::MissingTrailer => write!(f, "missing {} trailer", A::header_name()),
+            Self::InvalidTrailer(err) => write!(f, "invalid {} trailer: {err}", A::header_name()),
+        }
+    }
+}
+
+impl<E, A> std::error::Error for VerifyBodyError<E, A>
+where
+    E: std::error::Error + 'static,
+    A: Algorithm,
+{
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Body(err) => Some(err),
+            Self::Mismatch(mismatch) => Some(mismatch),
+            Self::MissingTrailer => None,
+            Self::InvalidTrailer(err) => Some(err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ContentMd5, VerifyBodyError, VerifyContentMd5};
+    use bytes::Bytes;
+    use http_body::Frame;
+    use http_body_util::{BodyExt, StreamBody};
+    use std::convert::Infallible;
+
+    fn chunked(
+        chunks: &[&'static str],
+    ) -> StreamBody<impl futures_util::Stream<Item = Result<Frame<Bytes>, Infallible>>> {
+        let frames = chunks
+            .iter()
+            .map(|chunk| Ok(Frame::data(Bytes::from_static(chunk.as_bytes()))))
+            .collect::<Vec<_>>();
+        StreamBody::new(futures_util::stream::iter(frames))
+    }
+
+    #[tokio::test]
+    async fn passes_matching_body() {
+        let expected = ContentMd5::compute(b"Check Integrity!");
+        let body = VerifyContentMd5::new(chunked(&["Check ", "Integrity", "!"]), expected);
+        let bytes = body.collect().await.unwrap().to_bytes();
+        assert_eq!(bytes, "Check Integrity!");
+    }
+
+    #[tokio::test]
+    async fn fails_on_mismatch() {
+        let expected = ContentMd5::compute(b"Check Integrity!");
+        let mut body = VerifyContentMd5::new(chunked(&["Check ", "Corruption!"]), expected);
+
+        assert!(body.frame().await.unwrap().is_ok());
+        assert!(body.frame().await.unwrap().is_ok());
+        match body.frame().await {
+            Some(Err(VerifyBodyError::Mismatch(mismatch))) => {
+                assert_eq!(mismatch.expected, expected);
+                assert_eq!(mismatch.actual, ContentMd5::compute(b"Check Corruption!"));
+            }
+            other => panic!("expected a mismatch, got {other:?}"),
+        }
+        assert!(body.frame().await.is_none());
+    }
+}
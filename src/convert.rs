@@ -0,0 +1,218 @@
+//! Conversions between [`DigestHeader`] and its textual and binary representations.
+
+use crate::algorithm::{Algorithm, MAX_DIGEST_LEN};
+use crate::{ContentMd5, DigestError, DigestHeader};
+use base64::{engine::general_purpose::STANDARD as base64, Engine};
+use headers::HeaderValue;
+use std::fmt;
+use std::str::FromStr;
+
+/// The length of the longest supported digest in padded base64.
+const MAX_BASE64_LEN: usize = MAX_DIGEST_LEN.div_ceil(3) * 4;
+
+impl<A: Algorithm> DigestHeader<A> {
+    /// The length of the canonical, padded base64 encoding of a digest.
+    pub(crate) const BASE64_LEN: usize = {
+        assert!(A::DIGEST_LEN <= MAX_DIGEST_LEN, "digest is too long");
+        A::DIGEST_LEN.div_ceil(3) * 4
+    };
+
+    /// Decodes the canonical base64 form used by the header.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use headers_content_md5::ContentMd5;
+    ///
+    /// let md5 = ContentMd5::from_base64("Q2hlY2sgSW50ZWdyaXR5IQ==").unwrap();
+    /// assert_eq!(md5.0, "Check Integrity!".as_bytes());
+    /// ```
+    pub fn from_base64(value: &str) -> Result<Self, DigestError> {
+        // Only accept the canonical, padded base64 encoding of a digest of the expected length.
+        if value.len() != Self::BASE64_LEN {
+            return Err(DigestError::InvalidLength { len: value.len() });
+        }
+
+        let mut buffer = [0; MAX_DIGEST_LEN + 2];
+        let len = base64
+            .decode_slice(value, &mut buffer)
+            .map_err(|_| DigestError::InvalidBase64)?;
+        Self::try_from(&buffer[..len])
+    }
+
+    /// Returns the canonical base64 form used by the header.
+    pub fn to_base64(&self) -> String {
+        base64.encode(self.0)
+    }
+
+    /// Returns the header value for this digest.
+    pub fn to_header_value(&self) -> HeaderValue {
+        let mut buffer = [0; MAX_BASE64_LEN];
+        let len = self.encode_base64(&mut buffer);
+        HeaderValue::from_bytes(&buffer[..len])
+            .expect("base64 characters are valid header value characters")
+    }
+
+    /// Decodes a hex digest, as printed by `md5sum` and `sha256sum`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use headers_content_md5::ContentMd5;
+    ///
+    /// let md5 = ContentMd5::from_hex("436865636b20496e7465677269747921").unwrap();
+    /// assert_eq!(md5.to_hex(), "436865636b20496e7465677269747921");
+    /// assert_eq!(md5.to_string(), "Q2hlY2sgSW50ZWdyaXR5IQ==");
+    /// ```
+    pub fn from_hex(value: &str) -> Result<Self, DigestError> {
+        decode_hex::<A>(value).ok_or(DigestError::InvalidHex)
+    }
+
+    /// Returns the digest as lowercase hex characters.
+    pub fn to_hex(&self) -> String {
+        self.0
+            .as_ref()
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect()
+    }
+
+    /// Encodes the canonical base64 form into `buffer`, returning the encoded length.
+    fn encode_base64(&self, buffer: &mut [u8; MAX_BASE64_LEN]) -> usize {
+        base64
+            .encode_slice(self.0, buffer)
+            .expect("supported digests always fit the buffer")
+    }
+}
+
+impl ContentMd5 {
+    /// Encodes the canonical base64 form into a stack buffer, without allocating.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use headers_content_md5::ContentMd5;
+    ///
+    /// let md5 = ContentMd5(*b"Check Integrity!");
+    /// assert_eq!(&md5.to_base64_array(), b"Q2hlY2sgSW50ZWdyaXR5IQ==");
+    /// ```
+    pub fn to_base64_array(&self) -> [u8; 24] {
+        let mut buffer = [0; 24];
+        base64
+            .encode_slice(self.0, &mut buffer)
+            .expect("16 bytes always encode to 24 base64 characters");
+        buffer
+    }
+}
+
+/// Formats the digest in its base64 header form.
+impl<A: Algorithm> fmt::Display for DigestHeader<A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut buffer = [0; MAX_BASE64_LEN];
+        let len = self.encode_base64(&mut buffer);
+        f.write_str(std::str::from_utf8(&buffer[..len]).map_err(|_| fmt::Error)?)
+    }
+}
+
+/// Parses the digest from its base64 header form.
+impl<A: Algorithm> FromStr for DigestHeader<A> {
+    type Err = DigestError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_base64(s)
+    }
+}
+
+impl<A: Algorithm> AsRef<[u8]> for DigestHeader<A> {
+    fn as_ref(&self) -> &[u8] {
+        self.0.as_ref()
+    }
+}
+
+impl From<[u8; 16]> for ContentMd5 {
+    fn from(digest: [u8; 16]) -> Self {
+        Self(digest)
+    }
+}
+
+impl From<ContentMd5> for [u8; 16] {
+    fn from(md5: ContentMd5) -> Self {
+        md5.0
+    }
+}
+
+impl<A: Algorithm> TryFrom<&[u8]> for DigestHeader<A> {
+    type Error = DigestError;
+
+    fn try_from(digest: &[u8]) -> Result<Self, Self::Error> {
+        A::Digest::try_from(digest)
+            .map(Self)
+            .map_err(|_| DigestError::InvalidDigestLength { len: digest.len() })
+    }
+}
+
+/// Decodes a hex digest of algorithm `A`.
+pub(crate) fn decode_hex<A: Algorithm>(value: &str) -> Option<DigestHeader<A>> {
+    if value.len() != 2 * A::DIGEST_LEN || !value.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    let mut digest = [0; MAX_DIGEST_LEN];
+    for (byte, pair) in digest.iter_mut().zip(value.as_bytes().chunks_exact(2)) {
+        *byte = u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok()?;
+    }
+    DigestHeader::try_from(&digest[..A::DIGEST_LEN]).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::algorithm::Sha512;
+    use crate::{ContentMd5, ContentMd5Error, DigestError, DigestHeader};
+
+    const DIGEST: [u8; 16] = *b"Check Integrity!";
+
+    #[test]
+    fn round_trips_representations() {
+        let md5 = ContentMd5::from(DIGEST);
+        assert_eq!(md5.to_string().parse::<ContentMd5>(), Ok(md5));
+        assert_eq!(ContentMd5::from_base64(&md5.to_base64()), Ok(md5));
+        assert_eq!(ContentMd5::from_hex(&md5.to_hex()), Ok(md5));
+        assert_eq!(ContentMd5::from_hex(&md5.to_hex().to_uppercase()), Ok(md5));
+        assert_eq!(ContentMd5::try_from(md5.as_ref()), Ok(md5));
+        assert_eq!(<[u8; 16]>::from(md5), DIGEST);
+    }
+
+    #[test]
+    fn rejects_invalid_input() {
+        assert_eq!(
+            ContentMd5::try_from(&DIGEST[..15]),
+            Err(ContentMd5Error::InvalidDigestLength { len: 15 })
+        );
+        assert_eq!(
+            ContentMd5::from_hex("436865636b20496e746567726974792"),
+            Err(ContentMd5Error::InvalidHex)
+        );
+        assert_eq!(
+            ContentMd5::from_hex("+36865636b20496e7465677269747921"),
+            Err(ContentMd5Error::InvalidHex)
+        );
+        assert_eq!(
+            "Q2hlY2sgSW50ZWdyaXR5IQ".parse::<ContentMd5>(),
+            Err(ContentMd5Error::InvalidLength { len: 22 })
+        );
+    }
+
+    #[test]
+    fn round_trips_longer_digests() {
+        let hex = "cf83e1357eefb8bdf1542850d66d8007d620e4050b5715dc83f4a921d36ce9ce\
+                   47d0d13c5d85f2b0ff8318d2877eec2f63b931bd47417a81a538327af927da3e";
+        let sha512 = DigestHeader::<Sha512>::from_hex(hex).unwrap();
+        assert_eq!(sha512.to_string().len(), 88);
+        assert_eq!(sha512.to_string().parse(), Ok(sha512));
+        assert_eq!(sha512.to_header_value(), sha512.to_string());
+        assert_eq!(
+            DigestHeader::<Sha512>::from_hex(&hex[..64]),
+            Err(DigestError::InvalidHex)
+        );
+    }
+}
@@ -0,0 +1,279 @@
+//! Vendor-specific spellings of the MD5 digest used by cloud storage services.
+
+use crate::{ContentMd5, DigestError, MultipleValues};
+use base64::{engine::general_purpose::STANDARD as base64, Engine};
+use headers::{Header, HeaderValue};
+use http::header::HeaderName;
+
+static X_GOOG_HASH: HeaderName = HeaderName::from_static("x-goog-hash");
+static X_MS_BLOB_CONTENT_MD5: HeaderName = HeaderName::from_static("x-ms-blob-content-md5");
+static X_MS_CONTENT_MD5: HeaderName = HeaderName::from_static("x-ms-content-md5");
+
+/// Generates a typed header carrying a single base64 MD5 digest under a vendor-specific name.
+macro_rules! md5_dialect {
+    ($(#[$meta:meta])* $header:ident, $name:ident) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        pub struct $header(pub ContentMd5);
+
+        impl From<ContentMd5> for $header {
+            fn from(digest: ContentMd5) -> Self {
+                Self(digest)
+            }
+        }
+
+        impl From<$header> for ContentMd5 {
+            fn from(header: $header) -> Self {
+                header.0
+            }
+        }
+
+        /// Decodes and encodes like [`ContentMd5`](type@ContentMd5).
+        impl Header for $header {
+            fn name() -> &'static HeaderName {
+                &$name
+            }
+
+            fn decode<'i, I: Iterator<Item = &'i HeaderValue>>(
+                values: &mut I,
+            ) -> Result<Self, headers::Error> {
+                Ok(Self(ContentMd5::decode_with(values, MultipleValues::default())?))
+            }
+
+            fn encode<E: Extend<HeaderValue>>(&self, values: &mut E) {
+                self.0.encode(values);
+            }
+        }
+    };
+}
+
+md5_dialect! {
+    /// `x-ms-blob-content-md5` header of Azure Blob Storage, carrying the MD5 digest of a
+    /// whole blob.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use headers::HeaderMapExt;
+    /// use http::{HeaderMap, HeaderValue};
+    /// use headers_content_md5::{ContentMd5, MsBlobContentMd5};
+    ///
+    /// let mut headers = HeaderMap::new();
+    /// headers.insert(
+    ///     "x-ms-blob-content-md5",
+    ///     HeaderValue::from_static("1B2M2Y8AsgTpgAmY7PhCfg=="),
+    /// );
+    ///
+    /// let md5: ContentMd5 = headers.typed_get::<MsBlobContentMd5>().unwrap().into();
+    /// assert_eq!(md5.to_hex(), "d41d8cd98f00b204e9800998ecf8427e");
+    /// ```
+    MsBlobContentMd5, X_MS_BLOB_CONTENT_MD5
+}
+
+md5_dialect! {
+    /// `x-ms-content-md5` header of Azure Storage, carrying the MD5 digest of a request or
+    /// response body, such as a single block or range.
+    ///
+    /// Decoded and encoded like [`MsBlobContentMd5`].
+    MsContentMd5, X_MS_CONTENT_MD5
+}
+
+/// `x-goog-hash` header of Google Cloud Storage, carrying the MD5 and CRC32C checksums of an
+/// object.
+///
+/// ## Example values
+///
+/// * `crc32c=n03x6A==, md5=Ojk9c3dhfxgoKVVHYwFbHQ==`
+///
+/// The checksums may also be spread across several header values. Checksums of other types
+/// are ignored, and a checksum repeated with a different value is rejected. When encoding,
+/// each checksum is sent as its own header value.
+///
+/// # Example
+///
+/// ```
+/// use headers::HeaderMapExt;
+/// use http::{HeaderMap, HeaderValue};
+/// use headers_content_md5::{ContentMd5, GoogHash, MsBlobContentMd5};
+///
+/// let mut google = HeaderMap::new();
+/// google.append("x-goog-hash", HeaderValue::from_static("crc32c=AAAAAA=="));
+/// google.append("x-goog-hash", HeaderValue::from_static("md5=1B2M2Y8AsgTpgAmY7PhCfg=="));
+///
+/// let hash = google.typed_get::<GoogHash>().unwrap();
+/// assert_eq!(hash.crc32c(), Some(0));
+///
+/// let mut azure = HeaderMap::new();
+/// azure.typed_insert(MsBlobContentMd5(hash.md5().unwrap()));
+/// assert_eq!(azure["x-ms-blob-content-md5"], "1B2M2Y8AsgTpgAmY7PhCfg==");
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct GoogHash {
+    md5: Option<ContentMd5>,
+    crc32c: Option<u32>,
+}
+
+impl GoogHash {
+    /// Creates a header without any checksums.
+    ///
+    /// Such a header encodes to no values, so inserting it leaves an existing `x-goog-hash` header
+    /// untouched.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the MD5 digest.
+    pub fn with_md5(mut self, md5: ContentMd5) -> Self {
+        self.md5 = Some(md5);
+        self
+    }
+
+    /// Sets the CRC32C checksum.
+    pub fn with_crc32c(mut self, crc32c: u32) -> Self {
+        self.crc32c = Some(crc32c);
+        self
+    }
+
+    /// Returns the MD5 digest, if present.
+    pub fn md5(&self) -> Option<ContentMd5> {
+        self.md5
+    }
+
+    /// Returns the CRC32C checksum, if present.
+    pub fn crc32c(&self) -> Option<u32> {
+        self.crc32c
+    }
+
+    /// Decodes all values of the header, reporting why they were rejected.
+    ///
+    /// This applies the same validation as [`Header::decode`].
+    pub fn decode_values<'i>(
+        values: impl IntoIterator<Item = &'i HeaderValue>,
+    ) -> Result<Self, DigestError> {
+        let mut hash = Self::new();
+        let mut found = false;
+        for value in values {
+            let value = value.to_str().map_err(|_| DigestError::NotAscii)?;
+            for element in value.split(',') {
+                let (name, checksum) = element
+                    .trim()
+                    .split_once('=')
+                    .ok_or(DigestError::InvalidDictionary)?;
+                found = true;
+                if name.eq_ignore_ascii_case("md5") {
+                    set_once(&mut hash.md5, ContentMd5::from_base64(checksum)?)?;
+                } else if name.eq_ignore_ascii_case("crc32c") {
+                    set_once(&mut hash.crc32c, decode_crc32c(checksum)?)?;
+                }
+            }
+        }
+        if !found {
+            return Err(DigestError::Missing);
+        }
+        Ok(hash)
+    }
+}
+
+/// Stores a checksum, rejecting a repeated checksum of the same type that disagrees with it.
+fn set_once<T: PartialEq>(slot: &mut Option<T>, value: T) -> Result<(), DigestError> {
+    match slot {
+        Some(existing) if *existing != value => Err(DigestError::ConflictingValues),
+        _ => {
+            *slot = Some(value);
+            Ok(())
+        }
+    }
+}
+
+/// Decodes a base64-encoded, big-endian CRC32C checksum.
+fn decode_crc32c(value: &str) -> Result<u32, DigestError> {
+    if value.len() != 8 {
+        return Err(DigestError::InvalidLength { len: value.len() });
+    }
+    let mut buffer = [0; 6];
+    let len = base64
+        .decode_slice(value, &mut buffer)
+        .map_err(|_| DigestError::InvalidBase64)?;
+    let bytes = <[u8; 4]>::try_from(&buffer[..len])
+        .map_err(|_| DigestError::InvalidDigestLength { len })?;
+    Ok(u32::from_be_bytes(bytes))
+}
+
+impl From<ContentMd5> for GoogHash {
+    fn from(md5: ContentMd5) -> Self {
+        Self::new().with_md5(md5)
+    }
+}
+
+impl Header for GoogHash {
+    fn name() -> &'static HeaderName {
+        &X_GOOG_HASH
+    }
+
+    fn decode<'i, I: Iterator<Item = &'i HeaderValue>>(
+        values: &mut I,
+    ) -> Result<Self, headers::Error> {
+        Ok(Self::decode_values(values)?)
+    }
+
+    fn encode<E: Extend<HeaderValue>>(&self, values: &mut E) {
+        let crc32c = self
+            .crc32c
+            .map(|crc32c| format!("crc32c={}", base64.encode(crc32c.to_be_bytes())));
+        let md5 = self.md5.map(|md5| format!("md5={md5}"));
+        values.extend(crc32c.into_iter().chain(md5).map(|value| {
+            HeaderValue::from_str(&value).expect("base64 checksums are valid header values")
+        }));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ContentMd5, DigestError, GoogHash, MsContentMd5};
+    use headers::Header;
+    use http::HeaderValue;
+
+    const MD5: &str = "1B2M2Y8AsgTpgAmY7PhCfg==";
+
+    #[test]
+    fn round_trips_goog_hash() {
+        let value = HeaderValue::from_str(&format!("crc32c=n03x6A==, MD5={MD5}, sha=x")).unwrap();
+        let hash = GoogHash::decode(&mut [&value].into_iter()).unwrap();
+        assert_eq!(hash.md5(), Some(MD5.parse().unwrap()));
+        assert_eq!(hash.crc32c(), Some(0x9f4d_f1e8));
+
+        let mut values = Vec::new();
+        hash.encode(&mut values);
+        assert_eq!(values, ["crc32c=n03x6A==", &format!("md5={MD5}")]);
+
+        for value in ["md5", "crc32c=AAAA", "md5=AAAA", ""] {
+            assert!(
+                GoogHash::decode_values([&HeaderValue::from_static(value)]).is_err(),
+                "{value:?}"
+            );
+        }
+        assert_eq!(GoogHash::decode_values([]), Err(DigestError::Missing));
+
+        let repeated = HeaderValue::from_str(&format!("md5={MD5}")).unwrap();
+        assert!(GoogHash::decode_values([&value, &repeated]).is_ok());
+        for conflicting in ["md5=Q2hlY2sgSW50ZWdyaXR5IQ==", "crc32c=AAAAAA=="] {
+            assert_eq!(
+                GoogHash::decode_values([&value, &HeaderValue::from_static(conflicting)]),
+                Err(DigestError::ConflictingValues),
+                "{conflicting:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn translates_ms_content_md5() {
+        let value = HeaderValue::from_static(MD5);
+        let header = MsContentMd5::decode(&mut [&value].into_iter()).unwrap();
+        assert_eq!(ContentMd5::from(header), MD5.parse().unwrap());
+        assert_eq!(MsContentMd5::name(), "x-ms-content-md5");
+
+        let mut values = Vec::new();
+        header.encode(&mut values);
+        assert_eq!(values, [value]);
+    }
+}
@@ -0,0 +1,291 @@
+//! The `Content-Digest` and `Repr-Digest` fields of
+//! [RFC 9530](https://datatracker.ietf.org/doc/html/rfc9530).
+
+use crate::algorithm::{Algorithm, Md5, Sha1, Sha256, Sha512};
+use crate::structured::{self, BareItem};
+use crate::{ContentMd5, DigestError, DigestHeader};
+use headers::{Header, HeaderValue};
+use http::header::HeaderName;
+use std::fmt;
+use std::str::FromStr;
+
+static CONTENT_DIGEST: HeaderName = HeaderName::from_static("content-digest");
+static REPR_DIGEST: HeaderName = HeaderName::from_static("repr-digest");
+
+/// The digest lengths of the algorithms known to this crate, enforced while decoding.
+const KNOWN_LENGTHS: [(&str, usize); 4] = [
+    (Md5::NAME, Md5::DIGEST_LEN),
+    (Sha1::NAME, Sha1::DIGEST_LEN),
+    (Sha256::NAME, Sha256::DIGEST_LEN),
+    (Sha512::NAME, Sha512::DIGEST_LEN),
+];
+
+/// Parses a dictionary of digests into `entries`, rejecting algorithms that are repeated with a
+/// different digest.
+fn parse_entries(value: &str, entries: &mut Vec<(String, Vec<u8>)>) -> Result<(), DigestError> {
+    let members = structured::parse_dictionary(value).ok_or(DigestError::InvalidDictionary)?;
+    for (algorithm, item) in members {
+        let BareItem::ByteSequence(digest) = item else {
+            return Err(DigestError::InvalidDictionary);
+        };
+        let expected_len = KNOWN_LENGTHS
+            .iter()
+            .find(|(name, _)| *name == algorithm)
+            .map(|(_, len)| *len);
+        if expected_len.is_some_and(|len| len != digest.len()) {
+            return Err(DigestError::InvalidDigestLength { len: digest.len() });
+        }
+        match entries.iter().find(|(name, _)| *name == algorithm) {
+            Some((_, existing)) if *existing != digest => {
+                return Err(DigestError::ConflictingValues);
+            }
+            Some(_) => {}
+            None => entries.push((algorithm, digest)),
+        }
+    }
+    Ok(())
+}
+
+fn insert_entry(entries: &mut Vec<(String, Vec<u8>)>, algorithm: String, digest: Vec<u8>) {
+    match entries.iter_mut().find(|(name, _)| *name == algorithm) {
+        Some(entry) => entry.1 = digest,
+        None => entries.push((algorithm, digest)),
+    }
+}
+
+/// Generates a typed header holding a dictionary of digests keyed by algorithm.
+macro_rules! digest_field {
+    ($(#[$meta:meta])* $field:ident, $name:ident) => {
+        $(#[$meta])*
+        #[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+        pub struct $field {
+            entries: Vec<(String, Vec<u8>)>,
+        }
+
+        impl $field {
+            /// Creates a field without any digests.
+            ///
+            /// An empty field encodes to no value at all, so passing it to
+            /// [`typed_insert`](headers::HeaderMapExt::typed_insert) leaves an existing field in
+            /// place; use [`HeaderMap::remove`](http::HeaderMap::remove) to clear it instead.
+            pub fn new() -> Self {
+                Self::default()
+            }
+
+            /// Adds `digest`, replacing any digest of the same algorithm.
+            pub fn insert<A: Algorithm>(&mut self, digest: DigestHeader<A>) {
+                insert_entry(&mut self.entries, A::NAME.to_owned(), digest.as_ref().to_vec());
+            }
+
+            /// Adds `digest`, replacing any digest of the same algorithm.
+            pub fn with<A: Algorithm>(mut self, digest: DigestHeader<A>) -> Self {
+                self.insert(digest);
+                self
+            }
+
+            /// Returns the digest of algorithm `A`, if present.
+            pub fn get<A: Algorithm>(&self) -> Option<DigestHeader<A>> {
+                let (_, digest) = self.entries.iter().find(|(name, _)| name == A::NAME)?;
+                DigestHeader::try_from(digest.as_slice()).ok()
+            }
+
+            /// Returns the embedded `md5` digest as a [`ContentMd5`](type@ContentMd5), if present.
+            pub fn md5(&self) -> Option<ContentMd5> {
+                self.get::<Md5>()
+            }
+
+            /// Iterates over the algorithm tokens and raw digests, in field order.
+            pub fn iter(&self) -> impl Iterator<Item = (&str, &[u8])> {
+                self.entries
+                    .iter()
+                    .map(|(name, digest)| (name.as_str(), digest.as_slice()))
+            }
+
+            /// Returns the number of digests.
+            pub fn len(&self) -> usize {
+                self.entries.len()
+            }
+
+            /// Returns `true` if the field holds no digests.
+            pub fn is_empty(&self) -> bool {
+                self.entries.is_empty()
+            }
+
+            /// Decodes all values of the field, reporting why they were rejected.
+            ///
+            /// This applies the same validation as [`Header::decode`].
+            pub fn decode_values<'i>(
+                values: impl IntoIterator<Item = &'i HeaderValue>,
+            ) -> Result<Self, DigestError> {
+                let mut field = Self::new();
+                for value in values {
+                    let value = value.to_str().map_err(|_| DigestError::NotAscii)?;
+                    parse_entries(value, &mut field.entries)?;
+                }
+                if field.is_empty() {
+                    return Err(DigestError::Missing);
+                }
+                Ok(field)
+            }
+        }
+
+        impl<A: Algorithm> From<DigestHeader<A>> for $field {
+            fn from(digest: DigestHeader<A>) -> Self {
+                Self::new().with(digest)
+            }
+        }
+
+        impl Header for $field {
+            fn name() -> &'static HeaderName {
+                &$name
+            }
+
+            fn decode<'i, I: Iterator<Item = &'i HeaderValue>>(
+                values: &mut I,
+            ) -> Result<Self, headers::Error> {
+                Ok(Self::decode_values(values)?)
+            }
+
+            fn encode<E: Extend<HeaderValue>>(&self, values: &mut E) {
+                if self.is_empty() {
+                    return;
+                }
+                let value = HeaderValue::from_str(&self.to_string())
+                    .expect("serialized dictionaries are valid header values");
+                values.extend(std::iter::once(value));
+            }
+        }
+
+        /// Formats the field as a structured-field dictionary.
+        impl fmt::Display for $field {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                for (i, (name, digest)) in self.entries.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    f.write_str(&structured::serialize_byte_sequence(name, digest))?;
+                }
+                Ok(())
+            }
+        }
+
+        /// Parses the field from a structured-field dictionary.
+        impl FromStr for $field {
+            type Err = DigestError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                let mut field = Self::new();
+                parse_entries(s, &mut field.entries)?;
+                if field.is_empty() {
+                    return Err(DigestError::Missing);
+                }
+                Ok(field)
+            }
+        }
+    };
+}
+
+digest_field! {
+    /// `Content-Digest` header, defined in
+    /// [RFC 9530](https://datatracker.ietf.org/doc/html/rfc9530#section-2), carrying digests
+    /// of the message content.
+    ///
+    /// ## Example values
+    ///
+    /// * `sha-256=:47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU=:, md5=:1B2M2Y8AsgTpgAmY7PhCfg==:`
+    ///
+    /// Digests of the algorithms in [`algorithm`](crate::algorithm) must have the length of
+    /// that algorithm; digests of other algorithms are kept as-is. An algorithm may only be
+    /// repeated across field lines with the same digest.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use headers::HeaderMapExt;
+    /// use http::{HeaderMap, HeaderValue};
+    /// use headers_content_md5::{algorithm::Sha256, ContentDigest, ContentMd5};
+    ///
+    /// let mut headers = HeaderMap::new();
+    /// headers.insert(
+    ///     "content-digest",
+    ///     HeaderValue::from_static(
+    ///         "sha-256=:47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU=:, md5=:1B2M2Y8AsgTpgAmY7PhCfg==:",
+    ///     ),
+    /// );
+    ///
+    /// let digest = headers.typed_get::<ContentDigest>().unwrap();
+    /// assert_eq!(digest.md5(), Some("1B2M2Y8AsgTpgAmY7PhCfg==".parse::<ContentMd5>().unwrap()));
+    /// assert!(digest.get::<Sha256>().is_some());
+    /// ```
+    ContentDigest, CONTENT_DIGEST
+}
+
+digest_field! {
+    /// `Repr-Digest` header, defined in
+    /// [RFC 9530](https://datatracker.ietf.org/doc/html/rfc9530#section-3), carrying digests
+    /// of the selected representation.
+    ///
+    /// Parsed and validated like [`ContentDigest`].
+    ReprDigest, REPR_DIGEST
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::algorithm::Sha256;
+    use crate::{ContentDigest, ContentMd5, DigestError, DigestHeader, ReprDigest};
+    use headers::Header;
+    use http::HeaderValue;
+
+    const MD5: &str = "1B2M2Y8AsgTpgAmY7PhCfg==";
+    const SHA256: &str = "47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU=";
+
+    #[test]
+    fn decodes_dictionary() {
+        let a = HeaderValue::from_str(&format!("sha-256=:{SHA256}:, unknown=:AQ==:")).unwrap();
+        let b = HeaderValue::from_str(&format!("md5=:{MD5}:")).unwrap();
+        let digest = ContentDigest::decode(&mut [&a, &b].into_iter()).unwrap();
+
+        assert_eq!(digest.len(), 3);
+        assert_eq!(digest.md5(), Some(MD5.parse::<ContentMd5>().unwrap()));
+        assert_eq!(
+            digest.get::<Sha256>(),
+            Some(SHA256.parse::<DigestHeader<Sha256>>().unwrap())
+        );
+        assert_eq!(digest.iter().nth(1), Some(("unknown", &[1][..])));
+
+        let c = HeaderValue::from_static("md5=:Q2hlY2sgSW50ZWdyaXR5IQ==:");
+        assert!(ContentDigest::decode_values([&b, &b]).is_ok());
+        assert_eq!(
+            ContentDigest::decode_values([&a, &c, &b]),
+            Err(DigestError::ConflictingValues)
+        );
+    }
+
+    #[test]
+    fn encodes_dictionary() {
+        let digest = ReprDigest::new()
+            .with(SHA256.parse::<DigestHeader<Sha256>>().unwrap())
+            .with(MD5.parse::<ContentMd5>().unwrap());
+
+        let mut values = Vec::new();
+        digest.encode(&mut values);
+        assert_eq!(values[0], format!("sha-256=:{SHA256}:, md5=:{MD5}:"));
+        assert_eq!(values[0].to_str().unwrap().parse(), Ok(digest));
+    }
+
+    #[test]
+    fn rejects_invalid_values() {
+        assert_eq!(
+            "md5=:AQ==:".parse::<ContentDigest>(),
+            Err(DigestError::InvalidDigestLength { len: 1 })
+        );
+        assert_eq!("".parse::<ContentDigest>(), Err(DigestError::Missing));
+        for value in ["md5", "md5=1", "md5=:AQ==:,", "MD5=:AQ==:", ""] {
+            let value = HeaderValue::from_static(value);
+            assert!(
+                ContentDigest::decode(&mut [&value].into_iter()).is_err(),
+                "{value:?}"
+            );
+        }
+    }
+}
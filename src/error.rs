@@ -0,0 +1,66 @@
+use std::fmt;
+
+/// The reason a digest header value could not be decoded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DigestError {
+    /// No value was present.
+    Missing,
+    /// The value contains characters other than visible ASCII.
+    NotAscii,
+    /// The value does not have the length of an encoded digest.
+    InvalidLength {
+        /// The length of the value in bytes.
+        len: usize,
+    },
+    /// The value is not valid base64.
+    InvalidBase64,
+    /// The value decodes to something other than a digest of the expected length.
+    InvalidDigestLength {
+        /// The number of decoded bytes.
+        len: usize,
+    },
+    /// The value is not a hex digest of the expected length.
+    InvalidHex,
+    /// More than one value was present.
+    MultipleValues,
+    /// Multiple values were present and decoded to different digests.
+    ConflictingValues,
+    /// The value is not a valid structured-field dictionary of digests.
+    InvalidDictionary,
+    /// The value is not an entity tag carrying a digest.
+    InvalidEtag,
+}
+
+/// The reason a `Content-MD5` value could not be decoded.
+pub type ContentMd5Error = DigestError;
+
+impl fmt::Display for DigestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Missing => f.write_str("missing digest value"),
+            Self::NotAscii => f.write_str("digest value is not visible ASCII"),
+            Self::InvalidLength { len } => write!(f, "digest value has invalid length {len}"),
+            Self::InvalidBase64 => f.write_str("digest value is not valid base64"),
+            Self::InvalidDigestLength { len } => {
+                write!(
+                    f,
+                    "digest value decodes to an invalid length of {len} bytes"
+                )
+            }
+            Self::InvalidHex => f.write_str("digest value is not a valid hex digest"),
+            Self::MultipleValues => f.write_str("multiple digest values"),
+            Self::ConflictingValues => f.write_str("conflicting digest values"),
+            Self::InvalidDictionary => f.write_str("digest value is not a valid dictionary"),
+            Self::InvalidEtag => f.write_str("digest value is not a valid entity tag"),
+        }
+    }
+}
+
+impl std::error::Error for DigestError {}
+
+impl From<DigestError> for headers::Error {
+    fn from(_: DigestError) -> Self {
+        headers::Error::invalid()
+    }
+}
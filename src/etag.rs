@@ -0,0 +1,212 @@
+//! Conversions between [`DigestHeader`] and strong entity tags, and conditional request helpers.
+
+use crate::algorithm::Algorithm;
+use crate::{DigestError, DigestHeader};
+use headers::{ETag, Header, IfMatch, IfNoneMatch};
+
+/// How a digest is spelled inside an entity tag.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum EtagEncoding {
+    /// Lowercase hex, e.g. `"d41d8cd98f00b204e9800998ecf8427e"`, as used by Amazon S3.
+    #[default]
+    Hex,
+    /// Padded base64, e.g. `"1B2M2Y8AsgTpgAmY7PhCfg=="`, as used by the header itself.
+    Base64,
+}
+
+impl<A: Algorithm> DigestHeader<A> {
+    /// Returns a strong entity tag carrying the digest in the given `encoding`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use headers::ETag;
+    /// use headers_content_md5::{ContentMd5, EtagEncoding};
+    ///
+    /// let md5: ContentMd5 = "1B2M2Y8AsgTpgAmY7PhCfg==".parse().unwrap();
+    /// assert_eq!(
+    ///     md5.to_etag(EtagEncoding::Hex),
+    ///     "\"d41d8cd98f00b204e9800998ecf8427e\"".parse::<ETag>().unwrap()
+    /// );
+    /// assert_eq!(
+    ///     md5.to_etag(EtagEncoding::Base64),
+    ///     "\"1B2M2Y8AsgTpgAmY7PhCfg==\"".parse::<ETag>().unwrap()
+    /// );
+    /// ```
+    pub fn to_etag(&self, encoding: EtagEncoding) -> ETag {
+        let tag = match encoding {
+            EtagEncoding::Hex => self.to_hex(),
+            EtagEncoding::Base64 => self.to_base64(),
+        };
+        format!("\"{tag}\"")
+            .parse()
+            .expect("hex and base64 digests are valid entity tags")
+    }
+
+    /// Recovers the digest from a strong entity tag holding it in either [`EtagEncoding`].
+    ///
+    /// Weak tags, and tags that do not have the length of an encoded digest, are rejected with
+    /// [`DigestError::InvalidEtag`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use headers::ETag;
+    /// use headers_content_md5::{ContentMd5, DigestError};
+    ///
+    /// let etag: ETag = "\"d41d8cd98f00b204e9800998ecf8427e\"".parse().unwrap();
+    /// assert_eq!(ContentMd5::from_etag(&etag).unwrap().to_string(), "1B2M2Y8AsgTpgAmY7PhCfg==");
+    ///
+    /// let etag: ETag = "\"d41d8cd98f00b204e9800998ecf8427e-2\"".parse().unwrap();
+    /// assert_eq!(ContentMd5::from_etag(&etag), Err(DigestError::InvalidEtag));
+    /// ```
+    pub fn from_etag(etag: &ETag) -> Result<Self, DigestError> {
+        let tag = strong_tag(etag).ok_or(DigestError::InvalidEtag)?;
+        if tag.len() == 2 * A::DIGEST_LEN {
+            Self::from_hex(&tag)
+        } else if tag.len() == Self::BASE64_LEN {
+            Self::from_base64(&tag)
+        } else {
+            Err(DigestError::InvalidEtag)
+        }
+    }
+
+    /// Evaluates an `If-Match` precondition against the entity tag of a resource with this
+    /// digest.
+    ///
+    /// Returns `false` if a conditional write, such as a `PUT`, should be answered with
+    /// `412 Precondition Failed`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use headers::{IfMatch, HeaderMapExt};
+    /// use http::{HeaderMap, HeaderValue};
+    /// use headers_content_md5::{ContentMd5, EtagEncoding};
+    ///
+    /// let mut headers = HeaderMap::new();
+    /// headers.insert(
+    ///     "if-match",
+    ///     HeaderValue::from_static("\"d41d8cd98f00b204e9800998ecf8427e\""),
+    /// );
+    /// let if_match = headers.typed_get::<IfMatch>().unwrap();
+    ///
+    /// let md5: ContentMd5 = "1B2M2Y8AsgTpgAmY7PhCfg==".parse().unwrap();
+    /// assert!(md5.passes_if_match(&if_match, EtagEncoding::Hex));
+    /// assert!(!md5.passes_if_match(&if_match, EtagEncoding::Base64));
+    /// ```
+    pub fn passes_if_match(&self, if_match: &IfMatch, encoding: EtagEncoding) -> bool {
+        if_match.precondition_passes(&self.to_etag(encoding))
+    }
+
+    /// Evaluates an `If-None-Match` precondition against the entity tag of a resource with this
+    /// digest.
+    ///
+    /// Returns `false` if a conditional `GET` or `HEAD` should be answered with
+    /// `304 Not Modified`, or any other request with `412 Precondition Failed`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use headers::IfNoneMatch;
+    /// use headers_content_md5::{ContentMd5, EtagEncoding};
+    ///
+    /// let md5: ContentMd5 = "1B2M2Y8AsgTpgAmY7PhCfg==".parse().unwrap();
+    /// let if_none_match = IfNoneMatch::from(md5.to_etag(EtagEncoding::Hex));
+    /// assert!(!md5.passes_if_none_match(&if_none_match, EtagEncoding::Hex));
+    /// ```
+    pub fn passes_if_none_match(
+        &self,
+        if_none_match: &IfNoneMatch,
+        encoding: EtagEncoding,
+    ) -> bool {
+        if_none_match.precondition_passes(&self.to_etag(encoding))
+    }
+}
+
+/// Encodes the digest as a strong entity tag in [`EtagEncoding::Hex`].
+impl<A: Algorithm> From<DigestHeader<A>> for ETag {
+    fn from(digest: DigestHeader<A>) -> Self {
+        digest.to_etag(EtagEncoding::Hex)
+    }
+}
+
+/// Recovers the digest like [`DigestHeader::from_etag`].
+impl<A: Algorithm> TryFrom<&ETag> for DigestHeader<A> {
+    type Error = DigestError;
+
+    fn try_from(etag: &ETag) -> Result<Self, Self::Error> {
+        Self::from_etag(etag)
+    }
+}
+
+/// Returns the unquoted tag of a strong entity tag, or `None` for weak tags.
+pub(crate) fn strong_tag(etag: &ETag) -> Option<String> {
+    if etag.is_weak() {
+        return None;
+    }
+    let mut values = Vec::new();
+    etag.encode(&mut values);
+    let value = values.first()?.to_str().ok()?;
+    let tag = value.strip_prefix('"')?.strip_suffix('"')?;
+    Some(tag.to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::algorithm::Sha256;
+    use crate::{ContentMd5, DigestError, DigestHeader, EtagEncoding};
+    use headers::{ETag, IfMatch, IfNoneMatch};
+
+    const MD5: &str = "1B2M2Y8AsgTpgAmY7PhCfg==";
+
+    #[test]
+    fn round_trips_etags() {
+        let md5: ContentMd5 = MD5.parse().unwrap();
+        for encoding in [EtagEncoding::Hex, EtagEncoding::Base64] {
+            let etag = md5.to_etag(encoding);
+            assert_eq!(ContentMd5::try_from(&etag), Ok(md5), "{encoding:?}");
+        }
+        assert_eq!(ETag::from(md5), md5.to_etag(EtagEncoding::Hex));
+
+        let sha256 = DigestHeader::<Sha256>::new([7; 32]);
+        assert_eq!(
+            DigestHeader::<Sha256>::from_etag(&sha256.into()),
+            Ok(sha256)
+        );
+        assert_eq!(
+            ContentMd5::from_etag(&sha256.into()),
+            Err(DigestError::InvalidEtag)
+        );
+
+        for tag in [
+            "W/\"d41d8cd98f00b204e9800998ecf8427e\"",
+            "\"\"",
+            "\"xyzzy\"",
+        ] {
+            let etag: ETag = tag.parse().unwrap();
+            assert_eq!(
+                ContentMd5::from_etag(&etag),
+                Err(DigestError::InvalidEtag),
+                "{tag}"
+            );
+        }
+    }
+
+    #[test]
+    fn evaluates_preconditions() {
+        let md5: ContentMd5 = MD5.parse().unwrap();
+        let other = ContentMd5([0; 16]);
+        let etag = md5.to_etag(EtagEncoding::Base64);
+
+        let if_match = IfMatch::from(etag.clone());
+        assert!(md5.passes_if_match(&if_match, EtagEncoding::Base64));
+        assert!(!other.passes_if_match(&if_match, EtagEncoding::Base64));
+        assert!(other.passes_if_match(&IfMatch::any(), EtagEncoding::Base64));
+
+        let if_none_match = IfNoneMatch::from(etag);
+        assert!(!md5.passes_if_none_match(&if_none_match, EtagEncoding::Base64));
+        assert!(other.passes_if_none_match(&if_none_match, EtagEncoding::Base64));
+        assert!(!other.passes_if_none_match(&IfNoneMatch::any(), EtagEncoding::Hex));
+    }
+}
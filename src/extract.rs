@@ -0,0 +1,177 @@
+//! [`axum`](https://docs.rs/axum) extractors, available with the `axum` feature.
+
+use crate::algorithm::{Algorithm, Compute, Md5};
+use crate::{ContentMd5, DigestError, DigestHeader, DigestMismatch, MultipleValues};
+use axum_core::extract::rejection::BytesRejection;
+use axum_core::extract::{FromRequest, Request};
+use axum_core::response::{IntoResponse, Response};
+use bytes::Bytes;
+use headers::Header;
+use http::StatusCode;
+use std::fmt;
+
+/// Extracts the request body after verifying it against the [`DigestHeader`] of algorithm `A`.
+///
+/// # Example
+///
+/// ```
+/// use headers_content_md5::{algorithm::Md5, VerifiedDigestBody};
+///
+/// async fn upload(VerifiedDigestBody(bytes, md5): VerifiedDigestBody<Md5>) -> String {
+///     format!("received {} bytes with digest {md5}", bytes.len())
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct VerifiedDigestBody<A: Algorithm>(pub Bytes, pub DigestHeader<A>);
+
+impl<S, A> FromRequest<S> for VerifiedDigestBody<A>
+where
+    S: Send + Sync,
+    A: Compute,
+{
+    type Rejection = VerifiedDigestBodyRejection<A>;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let values = req.headers().get_all(DigestHeader::<A>::name());
+        let digest = match DigestHeader::decode_with(values, MultipleValues::default()) {
+            Ok(digest) => digest,
+            Err(DigestError::Missing) => return Err(VerifiedDigestBodyRejection::Missing),
+            Err(err) => return Err(VerifiedDigestBodyRejection::Malformed(err)),
+        };
+
+        let bytes = Bytes::from_request(req, state)
+            .await
+            .map_err(VerifiedDigestBodyRejection::Body)?;
+        digest
+            .verify(&bytes)
+            .map_err(VerifiedDigestBodyRejection::Mismatch)?;
+
+        Ok(Self(bytes, digest))
+    }
+}
+
+/// Extracts the request body after verifying it against the `Content-MD5` header.
+///
+/// # Example
+///
+/// ```
+/// use headers_content_md5::VerifiedMd5Body;
+///
+/// async fn upload(VerifiedMd5Body(bytes, md5): VerifiedMd5Body) -> String {
+///     format!("received {} bytes with digest {md5:?}", bytes.len())
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct VerifiedMd5Body(pub Bytes, pub ContentMd5);
+
+impl<S> FromRequest<S> for VerifiedMd5Body
+where
+    S: Send + Sync,
+{
+    type Rejection = VerifiedMd5BodyRejection;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let VerifiedDigestBody(bytes, md5) = VerifiedDigestBody::from_request(req, state).await?;
+        Ok(Self(bytes, md5))
+    }
+}
+
+/// The rejection returned by the [`VerifiedDigestBody`] extractor.
+#[derive(Debug)]
+pub enum VerifiedDigestBodyRejection<A: Algorithm = Md5> {
+    /// The request does not carry the header.
+    Missing,
+    /// The header could not be decoded.
+    Malformed(DigestError),
+    /// The body did not match the header.
+    Mismatch(DigestMismatch<A>),
+    /// The body could not be read.
+    Body(BytesRejection),
+}
+
+/// The rejection returned by the [`VerifiedMd5Body`] extractor.
+pub type VerifiedMd5BodyRejection = VerifiedDigestBodyRejection<Md5>;
+
+impl<A: Algorithm> fmt::Display for VerifiedDigestBodyRejection<A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = A::header_name();
+        match self {
+            Self::Missing => write!(f, "missing {name} header"),
+            Self::Malformed(err) => write!(f, "malformed {name} header: {err}"),
+            Self::Mismatch(mismatch) => mismatch.fmt(f),
+            Self::Body(rejection) => rejection.fmt(f),
+        }
+    }
+}
+
+impl<A: Algorithm> std::error::Error for VerifiedDigestBodyRejection<A> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Missing => None,
+            Self::Malformed(err) => Some(err),
+            Self::Mismatch(mismatch) => Some(mismatch),
+            Self::Body(rejection) => Some(rejection),
+        }
+    }
+}
+
+impl<A: Algorithm> IntoResponse for VerifiedDigestBodyRejection<A> {
+    fn into_response(self) -> Response {
+        match self {
+            Self::Body(rejection) => rejection.into_response(),
+            rejection => (StatusCode::BAD_REQUEST, rejection.to_string()).into_response(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ContentMd5, VerifiedMd5Body, VerifiedMd5BodyRejection};
+    use axum_core::body::Body;
+    use axum_core::extract::{FromRequest, Request};
+    use axum_core::response::IntoResponse;
+    use headers::HeaderMapExt;
+    use http::StatusCode;
+
+    fn request(md5: Option<&'static str>) -> Request {
+        let mut request = Request::new(Body::from("Check Integrity!"));
+        if let Some(md5) = md5 {
+            request
+                .headers_mut()
+                .insert("content-md5", md5.parse().unwrap());
+        }
+        request
+    }
+
+    #[tokio::test]
+    async fn extracts_verified_body() {
+        let md5 = ContentMd5::compute(b"Check Integrity!");
+        let mut req = request(None);
+        req.headers_mut().typed_insert(md5);
+
+        let VerifiedMd5Body(bytes, extracted) =
+            VerifiedMd5Body::from_request(req, &()).await.unwrap();
+        assert_eq!(bytes, "Check Integrity!");
+        assert_eq!(extracted, md5);
+    }
+
+    #[tokio::test]
+    async fn distinguishes_rejections() {
+        let rejection = VerifiedMd5Body::from_request(request(None), &())
+            .await
+            .unwrap_err();
+        assert!(matches!(rejection, VerifiedMd5BodyRejection::Missing));
+
+        let rejection = VerifiedMd5Body::from_request(request(Some("not base64!")), &())
+            .await
+            .unwrap_err();
+        assert!(matches!(rejection, VerifiedMd5BodyRejection::Malformed(_)));
+
+        let rejection =
+            VerifiedMd5Body::from_request(request(Some("1B2M2Y8AsgTpgAmY7PhCfg==")), &())
+                .await
+                .unwrap_err();
+        assert!(matches!(rejection, VerifiedMd5BodyRejection::Mismatch(_)));
+        assert_eq!(rejection.into_response().status(), StatusCode::BAD_REQUEST);
+    }
+}
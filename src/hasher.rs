@@ -0,0 +1,107 @@
+//! Digest computation, available with the `md5`, `sha1` and `sha2` features.
+
+use crate::algorithm::Compute;
+use crate::DigestHeader;
+
+impl<A: Compute> DigestHeader<A> {
+    /// Computes the digest of the given body.
+    ///
+    /// # Example
+    ///
+    #[cfg_attr(feature = "md5", doc = "```")]
+    #[cfg_attr(not(feature = "md5"), doc = "```ignore")]
+    /// use headers::Header;
+    /// use headers_content_md5::ContentMd5;
+    ///
+    /// let md5 = ContentMd5::compute(b"");
+    ///
+    /// let mut header = Vec::default();
+    /// md5.encode(&mut header);
+    /// assert_eq!(header[0], "1B2M2Y8AsgTpgAmY7PhCfg==");
+    /// ```
+    pub fn compute(body: &[u8]) -> Self {
+        let mut hasher = DigestHasher::new();
+        hasher.update(body);
+        hasher.finalize()
+    }
+}
+
+/// Incrementally computes a [`DigestHeader`] over a body that arrives in chunks.
+///
+/// # Example
+///
+#[cfg_attr(feature = "md5", doc = "```")]
+#[cfg_attr(not(feature = "md5"), doc = "```ignore")]
+/// use headers_content_md5::{ContentMd5, ContentMd5Hasher};
+///
+/// let mut hasher = ContentMd5Hasher::new();
+/// hasher.update(b"Check ");
+/// hasher.update(b"Integrity!");
+/// assert_eq!(hasher.finalize(), ContentMd5::compute(b"Check Integrity!"));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct DigestHasher<A: Compute>(A::Hasher);
+
+/// Incrementally computes a [`ContentMd5`](type@crate::ContentMd5).
+#[cfg(feature = "md5")]
+pub type ContentMd5Hasher = DigestHasher<crate::Md5>;
+
+impl<A: Compute> DigestHasher<A> {
+    /// Creates a new hasher.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feeds the next chunk of the body into the hasher.
+    pub fn update(&mut self, data: impl AsRef<[u8]>) {
+        A::update(&mut self.0, data.as_ref());
+    }
+
+    /// Consumes the hasher and returns the digest of all chunks seen so far.
+    pub fn finalize(self) -> DigestHeader<A> {
+        DigestHeader(A::finalize(self.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "md5")]
+    use crate::{ContentMd5, ContentMd5Hasher};
+
+    #[cfg(feature = "md5")]
+    #[test]
+    fn compute_works() {
+        let md5 = ContentMd5::compute(b"");
+        assert_eq!(
+            md5.0,
+            [
+                0xd4, 0x1d, 0x8c, 0xd9, 0x8f, 0x00, 0xb2, 0x04, 0xe9, 0x80, 0x09, 0x98, 0xec, 0xf8,
+                0x42, 0x7e
+            ]
+        );
+    }
+
+    #[cfg(feature = "md5")]
+    #[test]
+    fn hasher_matches_compute() {
+        let mut hasher = ContentMd5Hasher::new();
+        for chunk in b"The quick brown fox jumps over the lazy dog".chunks(5) {
+            hasher.update(chunk);
+        }
+        assert_eq!(
+            hasher.finalize(),
+            ContentMd5::compute(b"The quick brown fox jumps over the lazy dog")
+        );
+    }
+
+    #[cfg(feature = "sha2")]
+    #[test]
+    fn computes_sha256() {
+        use crate::{algorithm::Sha256, DigestHeader};
+
+        assert_eq!(
+            DigestHeader::<Sha256>::compute(b"").to_hex(),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+    }
+}
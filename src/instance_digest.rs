@@ -0,0 +1,490 @@
+//! The legacy `Digest` and `Want-Digest` fields of
+//! [RFC 3230](https://datatracker.ietf.org/doc/html/rfc3230), carrying instance digests.
+
+use crate::algorithm::{Algorithm, Md5, Sha1, Sha256, Sha512};
+use crate::negotiate::negotiate;
+use crate::{ContentMd5, DigestError, DigestHeader};
+use headers::{Header, HeaderValue};
+use http::header::HeaderName;
+use std::fmt;
+use std::str::FromStr;
+
+static DIGEST: HeaderName = HeaderName::from_static("digest");
+static WANT_DIGEST: HeaderName = HeaderName::from_static("want-digest");
+
+/// Returns `true` if `value` is a non-empty RFC 9110 token.
+fn is_token(value: &str) -> bool {
+    !value.is_empty()
+        && value
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b))
+}
+
+/// Splits a comma-separated list, skipping empty elements.
+fn list_elements(value: &str) -> impl Iterator<Item = &str> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|element| !element.is_empty())
+}
+
+/// Checks the encoded digest of the algorithms known to this crate.
+fn validate(algorithm: &str, encoded: &str) -> Result<(), DigestError> {
+    fn check<A: Algorithm>(encoded: &str) -> Result<(), DigestError> {
+        DigestHeader::<A>::from_base64(encoded).map(drop)
+    }
+
+    match algorithm {
+        Md5::NAME => check::<Md5>(encoded),
+        Sha1::NAME => check::<Sha1>(encoded),
+        Sha256::NAME => check::<Sha256>(encoded),
+        Sha512::NAME => check::<Sha512>(encoded),
+        _ => Ok(()),
+    }
+}
+
+/// `Digest` header, defined in
+/// [RFC 3230](https://datatracker.ietf.org/doc/html/rfc3230#section-4.3.2).
+///
+/// ## Example values
+///
+/// * `MD5=Q2hlY2sgSW50ZWdyaXR5IQ==`
+/// * `SHA-256=47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU=, MD5=1B2M2Y8AsgTpgAmY7PhCfg==`
+///
+/// Algorithm tokens are case-insensitive. Digests of the algorithms in
+/// [`algorithm`](crate::algorithm) are validated as strictly as
+/// [`ContentMd5`](type@ContentMd5); digests of other algorithms, such as `UNIXsum`, are kept
+/// verbatim. An algorithm may only be repeated with the same digest.
+///
+/// # Example
+///
+/// ```
+/// use headers::HeaderMapExt;
+/// use http::{HeaderMap, HeaderValue};
+/// use headers_content_md5::{ContentMd5, Digest};
+///
+/// let mut headers = HeaderMap::new();
+/// headers.insert("digest", HeaderValue::from_static("MD5=Q2hlY2sgSW50ZWdyaXR5IQ=="));
+///
+/// let digest = headers.typed_get::<Digest>().unwrap();
+/// let md5 = digest.md5().unwrap();
+/// assert_eq!(md5.0, "Check Integrity!".as_bytes());
+/// assert_eq!(Digest::from(md5), digest);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Digest {
+    /// Lowercase algorithm tokens and their encoded digests.
+    entries: Vec<(String, String)>,
+}
+
+impl Digest {
+    /// Creates a field without any digests.
+    ///
+    /// An empty field is not encoded, so inserting it keeps any existing `Digest` field; remove
+    /// the field to clear it.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `digest`, replacing any digest of the same algorithm.
+    pub fn insert<A: Algorithm>(&mut self, digest: DigestHeader<A>) {
+        let encoded = digest.to_base64();
+        match self.entries.iter_mut().find(|(name, _)| name == A::NAME) {
+            Some(entry) => entry.1 = encoded,
+            None => self.entries.push((A::NAME.to_owned(), encoded)),
+        }
+    }
+
+    /// Adds `digest`, replacing any digest of the same algorithm.
+    pub fn with<A: Algorithm>(mut self, digest: DigestHeader<A>) -> Self {
+        self.insert(digest);
+        self
+    }
+
+    /// Returns the digest of algorithm `A`, if present.
+    pub fn get<A: Algorithm>(&self) -> Option<DigestHeader<A>> {
+        let (_, encoded) = self.entries.iter().find(|(name, _)| name == A::NAME)?;
+        DigestHeader::from_base64(encoded).ok()
+    }
+
+    /// Returns the `MD5` instance digest as a [`ContentMd5`](type@ContentMd5), if present.
+    pub fn md5(&self) -> Option<ContentMd5> {
+        self.get::<Md5>()
+    }
+
+    /// Iterates over the lowercase algorithm tokens and encoded digests, in field order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.entries
+            .iter()
+            .map(|(name, encoded)| (name.as_str(), encoded.as_str()))
+    }
+
+    /// Returns the number of digests.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if the field holds no digests.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Decodes all values of the field, reporting why they were rejected.
+    ///
+    /// This applies the same validation as [`Header::decode`].
+    pub fn decode_values<'i>(
+        values: impl IntoIterator<Item = &'i HeaderValue>,
+    ) -> Result<Self, DigestError> {
+        let mut digest = Self::new();
+        for value in values {
+            digest.parse_into(value.to_str().map_err(|_| DigestError::NotAscii)?)?;
+        }
+        if digest.is_empty() {
+            return Err(DigestError::Missing);
+        }
+        Ok(digest)
+    }
+
+    fn parse_into(&mut self, value: &str) -> Result<(), DigestError> {
+        for element in list_elements(value) {
+            let (algorithm, encoded) = element
+                .split_once('=')
+                .filter(|(algorithm, encoded)| is_token(algorithm) && !encoded.is_empty())
+                .ok_or(DigestError::InvalidDictionary)?;
+            let algorithm = algorithm.to_ascii_lowercase();
+            validate(&algorithm, encoded)?;
+
+            match self.entries.iter().find(|(name, _)| *name == algorithm) {
+                Some((_, existing)) if existing != encoded => {
+                    return Err(DigestError::ConflictingValues);
+                }
+                Some(_) => {}
+                None => self.entries.push((algorithm, encoded.to_owned())),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<A: Algorithm> From<DigestHeader<A>> for Digest {
+    fn from(digest: DigestHeader<A>) -> Self {
+        Self::new().with(digest)
+    }
+}
+
+impl Header for Digest {
+    fn name() -> &'static HeaderName {
+        &DIGEST
+    }
+
+    fn decode<'i, I: Iterator<Item = &'i HeaderValue>>(
+        values: &mut I,
+    ) -> Result<Self, headers::Error> {
+        Ok(Self::decode_values(values)?)
+    }
+
+    fn encode<E: Extend<HeaderValue>>(&self, values: &mut E) {
+        if self.is_empty() {
+            return;
+        }
+        let value = HeaderValue::from_str(&self.to_string())
+            .expect("instance digests are valid header values");
+        values.extend(std::iter::once(value));
+    }
+}
+
+/// Formats the field as a list of instance digests, with uppercase algorithm tokens.
+impl fmt::Display for Digest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (name, encoded)) in self.entries.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{}={encoded}", name.to_ascii_uppercase())?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses the field from a list of instance digests.
+impl FromStr for Digest {
+    type Err = DigestError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut digest = Self::new();
+        digest.parse_into(s)?;
+        if digest.is_empty() {
+            return Err(DigestError::Missing);
+        }
+        Ok(digest)
+    }
+}
+
+/// `Want-Digest` header, defined in
+/// [RFC 3230](https://datatracker.ietf.org/doc/html/rfc3230#section-4.3.1).
+///
+/// ## Example values
+///
+/// * `MD5`
+/// * `SHA-256;q=0.3, MD5;q=1, SHA;q=0`
+///
+/// Quality values range from `0` (not acceptable) to `1`, the default.
+///
+/// # Example
+///
+/// ```
+/// use headers::HeaderMapExt;
+/// use http::{HeaderMap, HeaderValue};
+/// use headers_content_md5::algorithm::{Algorithm, Md5, Sha256};
+/// use headers_content_md5::WantDigest;
+///
+/// let mut headers = HeaderMap::new();
+/// headers.insert("want-digest", HeaderValue::from_static("SHA-256;q=0.3, MD5"));
+///
+/// let want = headers.typed_get::<WantDigest>().unwrap();
+/// assert_eq!(want.negotiate(&[Sha256::NAME, Md5::NAME]), Some(Md5::NAME));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct WantDigest {
+    /// Lowercase algorithm tokens and their quality values in thousandths.
+    preferences: Vec<(String, u16)>,
+}
+
+impl WantDigest {
+    /// Creates a field without any preferences.
+    ///
+    /// An empty field is not encoded, so inserting it keeps any existing `Want-Digest` field.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the quality value for algorithm `A`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `q` is not within `0.0..=1.0`.
+    pub fn insert<A: Algorithm>(&mut self, q: f32) {
+        assert!((0.0..=1.0).contains(&q), "quality values range from 0 to 1");
+        let q = (q * 1000.0).round() as u16;
+        match self
+            .preferences
+            .iter_mut()
+            .find(|(name, _)| name == A::NAME)
+        {
+            Some(preference) => preference.1 = q,
+            None => self.preferences.push((A::NAME.to_owned(), q)),
+        }
+    }
+
+    /// Sets the quality value for algorithm `A`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `q` is not within `0.0..=1.0`.
+    pub fn with<A: Algorithm>(mut self, q: f32) -> Self {
+        self.insert::<A>(q);
+        self
+    }
+
+    /// Returns the quality value given to algorithm `A`, if it is mentioned.
+    pub fn quality<A: Algorithm>(&self) -> Option<f32> {
+        self.iter()
+            .find(|(name, _)| *name == A::NAME)
+            .map(|(_, q)| q)
+    }
+
+    /// Iterates over the lowercase algorithm tokens and their quality values, in field order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, f32)> {
+        self.preferences
+            .iter()
+            .map(|(name, q)| (name.as_str(), f32::from(*q) / 1000.0))
+    }
+
+    /// Picks the algorithm a server should emit.
+    ///
+    /// Behaves like [`WantContentDigest::negotiate`](crate::WantContentDigest::negotiate):
+    /// the supported algorithm with the highest non-zero quality value wins, with ties going to
+    /// the earlier entry of `supported`.
+    pub fn negotiate<'a>(&self, supported: &[&'a str]) -> Option<&'a str> {
+        negotiate(&self.preferences, supported)
+    }
+
+    fn parse_into(&mut self, value: &str) -> Result<(), DigestError> {
+        for element in list_elements(value) {
+            let mut parts = element.split(';').map(str::trim);
+            let algorithm = parts
+                .next()
+                .filter(|algorithm| is_token(algorithm))
+                .ok_or(DigestError::InvalidDictionary)?
+                .to_ascii_lowercase();
+            let mut q = 1000;
+            for parameter in parts {
+                let (name, value) = parameter
+                    .split_once('=')
+                    .ok_or(DigestError::InvalidDictionary)?;
+                if name.trim().eq_ignore_ascii_case("q") {
+                    q = parse_qvalue(value.trim()).ok_or(DigestError::InvalidDictionary)?;
+                }
+            }
+
+            match self
+                .preferences
+                .iter_mut()
+                .find(|(name, _)| *name == algorithm)
+            {
+                Some(preference) => preference.1 = q,
+                None => self.preferences.push((algorithm, q)),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Parses an RFC 9110 quality value into thousandths.
+fn parse_qvalue(value: &str) -> Option<u16> {
+    let (integer, fraction) = value.split_once('.').unwrap_or((value, ""));
+    if fraction.len() > 3 || !fraction.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let fraction = format!("{fraction:0<3}").parse::<u16>().ok()?;
+    match integer {
+        "0" => Some(fraction),
+        "1" if fraction == 0 => Some(1000),
+        _ => None,
+    }
+}
+
+impl Header for WantDigest {
+    fn name() -> &'static HeaderName {
+        &WANT_DIGEST
+    }
+
+    fn decode<'i, I: Iterator<Item = &'i HeaderValue>>(
+        values: &mut I,
+    ) -> Result<Self, headers::Error> {
+        let mut want = Self::new();
+        for value in values {
+            want.parse_into(value.to_str().map_err(|_| DigestError::NotAscii)?)?;
+        }
+        if want.preferences.is_empty() {
+            return Err(DigestError::Missing.into());
+        }
+        Ok(want)
+    }
+
+    fn encode<E: Extend<HeaderValue>>(&self, values: &mut E) {
+        if self.preferences.is_empty() {
+            return;
+        }
+        let value = HeaderValue::from_str(&self.to_string())
+            .expect("digest preferences are valid header values");
+        values.extend(std::iter::once(value));
+    }
+}
+
+/// Formats the field as a list of algorithms with quality values.
+impl fmt::Display for WantDigest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (name, q)) in self.preferences.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            f.write_str(&name.to_ascii_uppercase())?;
+            match q {
+                1000 => {}
+                0 => f.write_str(";q=0")?,
+                q => {
+                    let fraction = format!("{q:03}");
+                    write!(f, ";q=0.{}", fraction.trim_end_matches('0'))?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Parses the field from a list of algorithms with quality values.
+impl FromStr for WantDigest {
+    type Err = DigestError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut want = Self::new();
+        want.parse_into(s)?;
+        if want.preferences.is_empty() {
+            return Err(DigestError::Missing);
+        }
+        Ok(want)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::algorithm::{Algorithm, Md5, Sha1, Sha256};
+    use crate::{ContentMd5, Digest, DigestError, DigestHeader, WantDigest};
+    use headers::Header;
+    use http::HeaderValue;
+
+    const MD5: &str = "1B2M2Y8AsgTpgAmY7PhCfg==";
+    const SHA256: &str = "47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU=";
+
+    #[test]
+    fn round_trips_digest() {
+        let a = HeaderValue::from_str(&format!("sha-256={SHA256}, UNIXsum=30637")).unwrap();
+        let b = HeaderValue::from_str(&format!("md5={MD5}")).unwrap();
+        let digest = Digest::decode(&mut [&a, &b].into_iter()).unwrap();
+
+        assert_eq!(digest.md5(), Some(MD5.parse::<ContentMd5>().unwrap()));
+        assert_eq!(
+            digest.get::<Sha256>(),
+            Some(SHA256.parse::<DigestHeader<Sha256>>().unwrap())
+        );
+        assert_eq!(digest.iter().nth(1), Some(("unixsum", "30637")));
+
+        let mut values = Vec::new();
+        digest.encode(&mut values);
+        assert_eq!(
+            values[0],
+            format!("SHA-256={SHA256}, UNIXSUM=30637, MD5={MD5}")
+        );
+        assert_eq!(
+            Digest::from(digest.md5().unwrap()).to_string(),
+            format!("MD5={MD5}")
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_digest() {
+        let cases = [
+            (
+                "MD5=Q2hlY2sgSW50ZWdyaXR5IQ",
+                DigestError::InvalidLength { len: 22 },
+            ),
+            (
+                "MD5=Q2hlY2sgSW50ZWdyaXR5IQ==, md5=1B2M2Y8AsgTpgAmY7PhCfg==",
+                DigestError::ConflictingValues,
+            ),
+            ("MD5", DigestError::InvalidDictionary),
+            ("=abc", DigestError::InvalidDictionary),
+            ("", DigestError::Missing),
+        ];
+        for (value, expected) in cases {
+            assert_eq!(value.parse::<Digest>(), Err(expected), "{value:?}");
+        }
+        assert!(format!("MD5={MD5}, MD5={MD5}").parse::<Digest>().is_ok());
+    }
+
+    #[test]
+    fn negotiates_want_digest() {
+        let value = HeaderValue::from_static("SHA-256;q=0.3, md5, SHA;q=0");
+        let want = WantDigest::decode(&mut [&value].into_iter()).unwrap();
+        assert_eq!(want.quality::<Sha256>(), Some(0.3));
+        assert_eq!(want.quality::<Sha1>(), Some(0.0));
+        assert_eq!(want.negotiate(&[Sha256::NAME, Md5::NAME]), Some(Md5::NAME));
+        assert_eq!(want.negotiate(&[Sha1::NAME]), None);
+        assert_eq!(want.to_string(), "SHA-256;q=0.3, MD5, SHA;q=0");
+
+        for value in ["MD5;q=1.5", "MD5;q=0.1234", "MD5;q", ";q=1", ""] {
+            assert!(value.parse::<WantDigest>().is_err(), "{value:?}");
+        }
+    }
+}
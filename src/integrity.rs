@@ -0,0 +1,361 @@
+//! Collecting the expected digests from every supported integrity header of a message.
+
+use crate::algorithm::{Algorithm, Md5, Sha1, Sha256, Sha512};
+use crate::{ContentDigest, ContentMd5, Digest, DigestError, DigestHeader, MultipleValues};
+use headers::Header;
+use http::HeaderMap;
+use std::fmt;
+
+/// An integrity header consulted by [`ExpectedDigests::from_headers`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DigestSource {
+    /// The `Content-MD5` header.
+    ContentMd5,
+    /// The RFC 3230 `Digest` header.
+    Digest,
+    /// The RFC 9530 `Content-Digest` header.
+    ContentDigest,
+}
+
+impl fmt::Display for DigestSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::ContentMd5 => "Content-MD5",
+            Self::Digest => "Digest",
+            Self::ContentDigest => "Content-Digest",
+        })
+    }
+}
+
+/// The digests a message body is expected to match, merged from all of its integrity headers.
+///
+/// Only digests of the algorithms in [`algorithm`](crate::algorithm) are collected; digests of
+/// other algorithms are ignored.
+///
+/// # Example
+///
+/// ```
+/// use http::{HeaderMap, HeaderValue};
+/// use headers_content_md5::{ContentMd5, DigestSource, ExpectedDigests, IntegrityError};
+///
+/// let mut headers = HeaderMap::new();
+/// headers.insert("content-md5", HeaderValue::from_static("1B2M2Y8AsgTpgAmY7PhCfg=="));
+/// headers.insert("digest", HeaderValue::from_static("MD5=1B2M2Y8AsgTpgAmY7PhCfg=="));
+///
+/// let expected = ExpectedDigests::from_headers(&headers).unwrap();
+/// assert_eq!(expected.sources(), [DigestSource::ContentMd5, DigestSource::Digest]);
+/// assert_eq!(expected.md5(), "1B2M2Y8AsgTpgAmY7PhCfg==".parse::<ContentMd5>().ok());
+///
+/// headers.insert("digest", HeaderValue::from_static("MD5=Q2hlY2sgSW50ZWdyaXR5IQ=="));
+/// assert!(matches!(
+///     ExpectedDigests::from_headers(&headers),
+///     Err(IntegrityError::Conflict { .. })
+/// ));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ExpectedDigests {
+    entries: Vec<(&'static str, Vec<u8>)>,
+    sources: Vec<DigestSource>,
+}
+
+impl ExpectedDigests {
+    /// Collects the digests announced by the `Content-MD5`, `Digest` and `Content-Digest`
+    /// headers.
+    ///
+    /// Fails if any of these headers is malformed, or if two of them announce different
+    /// digests for the same algorithm. A message without any of them yields an empty set.
+    pub fn from_headers(headers: &HeaderMap) -> Result<Self, IntegrityError> {
+        let mut expected = Self::default();
+
+        let values = headers.get_all(ContentMd5::name());
+        if let Some(md5) = decode(DigestSource::ContentMd5, || {
+            ContentMd5::decode_with(values, MultipleValues::default())
+        })? {
+            expected.add(DigestSource::ContentMd5, Some(md5))?;
+        }
+
+        let values = headers.get_all(Digest::name());
+        if let Some(digest) = decode(DigestSource::Digest, || Digest::decode_values(values))? {
+            expected.add(DigestSource::Digest, digest.get::<Md5>())?;
+            expected.add(DigestSource::Digest, digest.get::<Sha1>())?;
+            expected.add(DigestSource::Digest, digest.get::<Sha256>())?;
+            expected.add(DigestSource::Digest, digest.get::<Sha512>())?;
+        }
+
+        let values = headers.get_all(ContentDigest::name());
+        if let Some(digest) = decode(DigestSource::ContentDigest, || {
+            ContentDigest::decode_values(values)
+        })? {
+            expected.add(DigestSource::ContentDigest, digest.get::<Md5>())?;
+            expected.add(DigestSource::ContentDigest, digest.get::<Sha1>())?;
+            expected.add(DigestSource::ContentDigest, digest.get::<Sha256>())?;
+            expected.add(DigestSource::ContentDigest, digest.get::<Sha512>())?;
+        }
+
+        Ok(expected)
+    }
+
+    /// Returns the expected digest of algorithm `A`, if any header announced one.
+    pub fn get<A: Algorithm>(&self) -> Option<DigestHeader<A>> {
+        let (_, digest) = self.entries.iter().find(|(name, _)| *name == A::NAME)?;
+        DigestHeader::try_from(digest.as_slice()).ok()
+    }
+
+    /// Returns the expected MD5 digest, if any header announced one.
+    pub fn md5(&self) -> Option<ContentMd5> {
+        self.get::<Md5>()
+    }
+
+    /// Iterates over the algorithm tokens and expected digests.
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, &[u8])> {
+        self.entries
+            .iter()
+            .map(|(name, digest)| (*name, digest.as_slice()))
+    }
+
+    /// Returns the headers the digests were collected from, in the order they were consulted.
+    pub fn sources(&self) -> &[DigestSource] {
+        &self.sources
+    }
+
+    /// Returns `true` if the message carried no supported integrity header.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Verifies `body` against every expected digest whose algorithm can be computed with the
+    /// enabled crate features.
+    ///
+    /// Fails with [`IntegrityError::Missing`] if there are no expected digests, and with
+    /// [`IntegrityError::Unsupported`] if none of them can be computed.
+    #[cfg(any(feature = "md5", feature = "sha1", feature = "sha2"))]
+    pub fn verify(&self, body: &[u8]) -> Result<(), IntegrityError> {
+        if self.is_empty() {
+            return Err(IntegrityError::Missing);
+        }
+
+        let verified = [
+            #[cfg(feature = "md5")]
+            self.verify_with::<Md5>(body)?,
+            #[cfg(feature = "sha1")]
+            self.verify_with::<Sha1>(body)?,
+            #[cfg(feature = "sha2")]
+            self.verify_with::<Sha256>(body)?,
+            #[cfg(feature = "sha2")]
+            self.verify_with::<Sha512>(body)?,
+        ];
+
+        if verified.contains(&true) {
+            Ok(())
+        } else {
+            Err(IntegrityError::Unsupported)
+        }
+    }
+
+    /// Verifies `body` against the expected digest of algorithm `A`, returning whether there
+    /// was one.
+    #[cfg(any(feature = "md5", feature = "sha1", feature = "sha2"))]
+    fn verify_with<A: crate::Compute>(&self, body: &[u8]) -> Result<bool, IntegrityError> {
+        match self.get::<A>() {
+            Some(expected) => expected
+                .verify(body)
+                .map(|()| true)
+                .map_err(|_| IntegrityError::Mismatch { algorithm: A::NAME }),
+            None => Ok(false),
+        }
+    }
+
+    fn add<A: Algorithm>(
+        &mut self,
+        source: DigestSource,
+        digest: Option<DigestHeader<A>>,
+    ) -> Result<(), IntegrityError> {
+        let Some(digest) = digest else {
+            return Ok(());
+        };
+        if !self.sources.contains(&source) {
+            self.sources.push(source);
+        }
+
+        match self.entries.iter().find(|(name, _)| *name == A::NAME) {
+            Some((_, existing)) if existing.as_slice() != digest.as_ref() => {
+                Err(IntegrityError::Conflict { algorithm: A::NAME })
+            }
+            Some(_) => Ok(()),
+            None => {
+                self.entries.push((A::NAME, digest.as_ref().to_vec()));
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Runs `decode`, treating a missing header as absent and attributing other errors to `source`.
+fn decode<T>(
+    source: DigestSource,
+    decode: impl FnOnce() -> Result<T, DigestError>,
+) -> Result<Option<T>, IntegrityError> {
+    match decode() {
+        Ok(value) => Ok(Some(value)),
+        Err(DigestError::Missing) => Ok(None),
+        Err(error) => Err(IntegrityError::Malformed { source, error }),
+    }
+}
+
+/// The reason [`ExpectedDigests`] could not be collected or verified.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum IntegrityError {
+    /// An integrity header could not be decoded.
+    Malformed {
+        /// The malformed header.
+        source: DigestSource,
+        /// Why it was rejected.
+        error: DigestError,
+    },
+    /// Two integrity headers announced different digests for the same algorithm.
+    Conflict {
+        /// The algorithm token, e.g. `md5`.
+        algorithm: &'static str,
+    },
+    /// The message carried no supported integrity header.
+    Missing,
+    /// None of the expected digests can be computed with the enabled crate features.
+    Unsupported,
+    /// The body did not match an expected digest.
+    Mismatch {
+        /// The algorithm token, e.g. `md5`.
+        algorithm: &'static str,
+    },
+}
+
+impl fmt::Display for IntegrityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Malformed { source, error } => write!(f, "malformed {source} header: {error}"),
+            Self::Conflict { algorithm } => {
+                write!(
+                    f,
+                    "integrity headers announce conflicting {algorithm} digests"
+                )
+            }
+            Self::Missing => f.write_str("missing integrity header"),
+            Self::Unsupported => f.write_str("no announced digest algorithm is supported"),
+            Self::Mismatch { algorithm } => write!(f, "{algorithm} digest mismatch"),
+        }
+    }
+}
+
+impl std::error::Error for IntegrityError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Malformed { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::algorithm::Sha256;
+    use crate::{ContentMd5, DigestError, DigestSource, ExpectedDigests, IntegrityError};
+    use http::{HeaderMap, HeaderValue};
+
+    const MD5: &str = "1B2M2Y8AsgTpgAmY7PhCfg==";
+    const SHA256: &str = "47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU=";
+
+    fn headers(entries: &[(&'static str, String)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in entries {
+            headers.append(*name, HeaderValue::from_str(value).unwrap());
+        }
+        headers
+    }
+
+    #[test]
+    fn merges_all_headers() {
+        let headers = headers(&[
+            ("content-md5", MD5.to_owned()),
+            ("digest", format!("MD5={MD5}, UNIXsum=0")),
+            ("content-digest", format!("sha-256=:{SHA256}:")),
+        ]);
+        let expected = ExpectedDigests::from_headers(&headers).unwrap();
+
+        assert_eq!(
+            expected.sources(),
+            [
+                DigestSource::ContentMd5,
+                DigestSource::Digest,
+                DigestSource::ContentDigest
+            ]
+        );
+        assert_eq!(expected.md5(), Some(MD5.parse::<ContentMd5>().unwrap()));
+        assert_eq!(expected.get::<Sha256>().unwrap().to_string(), SHA256);
+        assert_eq!(expected.iter().count(), 2);
+        assert!(ExpectedDigests::from_headers(&HeaderMap::new())
+            .unwrap()
+            .is_empty());
+
+        let mut unsupported = HeaderMap::new();
+        unsupported.insert("digest", HeaderValue::from_static("UNIXsum=0"));
+        let expected = ExpectedDigests::from_headers(&unsupported).unwrap();
+        assert!(expected.is_empty());
+        assert!(expected.sources().is_empty());
+    }
+
+    #[test]
+    fn reports_malformed_and_conflicting_headers() {
+        let headers_ = headers(&[
+            ("content-md5", MD5.to_owned()),
+            (
+                "content-digest",
+                "md5=:Q2hlY2sgSW50ZWdyaXR5IQ==:".to_owned(),
+            ),
+        ]);
+        assert_eq!(
+            ExpectedDigests::from_headers(&headers_),
+            Err(IntegrityError::Conflict { algorithm: "md5" })
+        );
+
+        let headers_ = headers(&[
+            ("content-md5", MD5.to_owned()),
+            (
+                "content-digest",
+                "md5=:Q2hlY2sgSW50ZWdyaXR5IQ==:".to_owned(),
+            ),
+            ("content-digest", format!("md5=:{MD5}:")),
+        ]);
+        assert_eq!(
+            ExpectedDigests::from_headers(&headers_),
+            Err(IntegrityError::Malformed {
+                source: DigestSource::ContentDigest,
+                error: DigestError::ConflictingValues,
+            })
+        );
+
+        let headers_ = headers(&[("digest", "MD5=not base64".to_owned())]);
+        assert_eq!(
+            ExpectedDigests::from_headers(&headers_),
+            Err(IntegrityError::Malformed {
+                source: DigestSource::Digest,
+                error: DigestError::InvalidLength { len: 10 },
+            })
+        );
+    }
+
+    #[cfg(feature = "md5")]
+    #[test]
+    fn verifies_body() {
+        let headers_ = headers(&[("digest", format!("MD5={MD5}"))]);
+        let expected = ExpectedDigests::from_headers(&headers_).unwrap();
+        assert_eq!(expected.verify(b""), Ok(()));
+        assert_eq!(
+            expected.verify(b"Check Integrity!"),
+            Err(IntegrityError::Mismatch { algorithm: "md5" })
+        );
+        assert_eq!(
+            ExpectedDigests::default().verify(b""),
+            Err(IntegrityError::Missing)
+        );
+    }
+}
@@ -0,0 +1,24 @@
+//! [`tower`](https://docs.rs/tower) middleware, available with the `tower` feature.
+
+mod request;
+mod response;
+
+pub use request::{
+    ContentMd5Layer, ContentMd5Mode, ContentMd5Rejection, ContentMd5Service, DefaultOnReject,
+    DigestRejection, OnReject, VerifyDigestLayer, VerifyDigestService, VerifyMode,
+};
+pub use response::{
+    SetContentMd5, SetContentMd5Body, SetContentMd5Layer, SetDigest, SetDigestBody, SetDigestLayer,
+};
+
+use std::future::Future;
+use std::pin::Pin;
+
+/// The default limit of 2 MiB on the bytes a layer buffers from a body.
+const DEFAULT_BODY_LIMIT: usize = 2 * 1024 * 1024;
+
+/// The boxed error type used for failing request bodies.
+pub type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// The boxed future returned by the services in this module.
+pub type BoxFuture<T, E> = Pin<Box<dyn Future<Output = Result<T, E>> + Send>>;
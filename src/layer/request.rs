@@ -0,0 +1,436 @@
+use super::{BoxError, BoxFuture, DEFAULT_BODY_LIMIT};
+use crate::algorithm::{Algorithm, Compute, Md5};
+use crate::{DigestError, DigestHeader, DigestMismatch, MultipleValues};
+use bytes::Bytes;
+use headers::Header;
+use http::{Request, Response, StatusCode};
+use http_body::Body;
+use http_body_util::{BodyExt, Full, LengthLimitError, Limited};
+use std::fmt;
+use std::marker::PhantomData;
+use std::task::{Context, Poll};
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// Controls how [`VerifyDigestLayer`] treats the digest header of incoming requests.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum VerifyMode {
+    /// Rejects requests without the header.
+    Required,
+    /// Verifies the body if the request carries the header.
+    #[default]
+    VerifyIfPresent,
+    /// Passes every request through without looking at the header.
+    ///
+    /// The body is still buffered, since the inner service receives it as [`Full<Bytes>`], but
+    /// the [body limit](VerifyDigestLayer::body_limit) does not apply.
+    Ignore,
+}
+
+/// Controls how [`ContentMd5Layer`] treats the `Content-MD5` header of incoming requests.
+pub type ContentMd5Mode = VerifyMode;
+
+/// The reason a request was rejected by [`VerifyDigestLayer`].
+#[derive(Debug)]
+pub enum DigestRejection<A: Algorithm = Md5> {
+    /// The header is required but was not sent.
+    Missing,
+    /// The header could not be decoded.
+    Malformed(DigestError),
+    /// The body did not match the header.
+    Mismatch(DigestMismatch<A>),
+    /// The body exceeded the [body limit](VerifyDigestLayer::body_limit).
+    TooLarge {
+        /// The maximum number of bytes that were accepted.
+        limit: usize,
+    },
+    /// The body could not be read.
+    Body(BoxError),
+}
+
+/// The reason a request was rejected by [`ContentMd5Layer`].
+pub type ContentMd5Rejection = DigestRejection<Md5>;
+
+impl<A: Algorithm> fmt::Display for DigestRejection<A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = A::header_name();
+        match self {
+            Self::Missing => write!(f, "missing {name} header"),
+            Self::Malformed(err) => write!(f, "malformed {name} header: {err}"),
+            Self::Mismatch(mismatch) => mismatch.fmt(f),
+            Self::TooLarge { limit } => write!(f, "request body exceeds {limit} bytes"),
+            Self::Body(err) => write!(f, "failed to read request body: {err}"),
+        }
+    }
+}
+
+impl<A: Algorithm> std::error::Error for DigestRejection<A> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Missing | Self::TooLarge { .. } => None,
+            Self::Malformed(err) => Some(err),
+            Self::Mismatch(mismatch) => Some(mismatch),
+            Self::Body(err) => Some(err.as_ref()),
+        }
+    }
+}
+
+/// Builds the response sent for a [`DigestRejection`].
+///
+/// Implemented for closures taking the rejection and returning a [`Response`].
+pub trait OnReject<B, A: Algorithm = Md5> {
+    /// Builds the response for `rejection`.
+    fn on_reject(&self, rejection: DigestRejection<A>) -> Response<B>;
+}
+
+impl<B, A, F> OnReject<B, A> for F
+where
+    A: Algorithm,
+    F: Fn(DigestRejection<A>) -> Response<B>,
+{
+    fn on_reject(&self, rejection: DigestRejection<A>) -> Response<B> {
+        self(rejection)
+    }
+}
+
+/// Responds to bodies exceeding the limit with an empty `413 Payload Too Large`, and to every
+/// other rejection with an empty `400 Bad Request`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DefaultOnReject;
+
+impl<B: Default, A: Algorithm> OnReject<B, A> for DefaultOnReject {
+    fn on_reject(&self, rejection: DigestRejection<A>) -> Response<B> {
+        let mut response = Response::new(B::default());
+        *response.status_mut() = match rejection {
+            DigestRejection::TooLarge { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+            _ => StatusCode::BAD_REQUEST,
+        };
+        response
+    }
+}
+
+/// A [`Layer`] that verifies request bodies against their [`DigestHeader`].
+///
+/// Request bodies are buffered so that a mismatch can be answered before the inner service is
+/// called; the inner service receives the buffered body as [`Full<Bytes>`]. Bodies that are
+/// verified and larger than the [body limit](Self::body_limit) are rejected with
+/// [`DigestRejection::TooLarge`].
+///
+/// # Example
+///
+/// ```
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// use bytes::Bytes;
+/// use http::{Request, Response, StatusCode};
+/// use http_body_util::Full;
+/// use headers_content_md5::{ContentMd5Layer, ContentMd5Mode};
+/// use std::convert::Infallible;
+/// use tower::{service_fn, Layer, ServiceExt};
+///
+/// async fn handler(_: Request<Full<Bytes>>) -> Result<Response<String>, Infallible> {
+///     Ok(Response::new(String::from("accepted")))
+/// }
+///
+/// let service = ContentMd5Layer::new(ContentMd5Mode::Required).layer(service_fn(handler));
+///
+/// let request = Request::new(Full::new(Bytes::from("Check Integrity!")));
+/// let response = service.oneshot(request).await.unwrap();
+/// assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct VerifyDigestLayer<A: Algorithm, R = DefaultOnReject> {
+    mode: VerifyMode,
+    multiple_values: MultipleValues,
+    body_limit: usize,
+    on_reject: R,
+    algorithm: PhantomData<A>,
+}
+
+/// A [`VerifyDigestLayer`] verifying request bodies against their `Content-MD5` header.
+pub type ContentMd5Layer<R = DefaultOnReject> = VerifyDigestLayer<Md5, R>;
+
+impl<A: Algorithm, R: Default> Default for VerifyDigestLayer<A, R> {
+    fn default() -> Self {
+        Self {
+            mode: VerifyMode::default(),
+            multiple_values: MultipleValues::default(),
+            body_limit: DEFAULT_BODY_LIMIT,
+            on_reject: R::default(),
+            algorithm: PhantomData,
+        }
+    }
+}
+
+impl<A: Algorithm> VerifyDigestLayer<A> {
+    /// Creates a layer operating in the given mode.
+    pub fn new(mode: VerifyMode) -> Self {
+        Self {
+            mode,
+            ..Self::default()
+        }
+    }
+
+    /// Creates a layer rejecting requests without the header.
+    pub fn required() -> Self {
+        Self::new(VerifyMode::Required)
+    }
+
+    /// Creates a layer verifying requests that carry the header.
+    pub fn verify_if_present() -> Self {
+        Self::new(VerifyMode::VerifyIfPresent)
+    }
+}
+
+impl<A: Algorithm, R> VerifyDigestLayer<A, R> {
+    /// Sets how requests with multiple header values are treated.
+    ///
+    /// Defaults to [`MultipleValues::RequireAgreement`].
+    pub fn multiple_values(mut self, policy: MultipleValues) -> Self {
+        self.multiple_values = policy;
+        self
+    }
+
+    /// Sets the maximum number of bytes buffered from a request body that is verified against
+    /// its header.
+    ///
+    /// Larger bodies are rejected with [`DigestRejection::TooLarge`]. Bodies of requests without
+    /// the header, and all bodies in [`VerifyMode::Ignore`], are passed on regardless of their
+    /// size. Defaults to 2 MiB.
+    pub fn body_limit(mut self, limit: usize) -> Self {
+        self.body_limit = limit;
+        self
+    }
+
+    /// Replaces the response sent for rejected requests.
+    pub fn on_reject<T>(self, on_reject: T) -> VerifyDigestLayer<A, T> {
+        VerifyDigestLayer {
+            mode: self.mode,
+            multiple_values: self.multiple_values,
+            body_limit: self.body_limit,
+            on_reject,
+            algorithm: PhantomData,
+        }
+    }
+}
+
+impl<S, A: Algorithm, R: Clone> Layer<S> for VerifyDigestLayer<A, R> {
+    type Service = VerifyDigestService<S, A, R>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        VerifyDigestService {
+            inner,
+            mode: self.mode,
+            multiple_values: self.multiple_values,
+            body_limit: self.body_limit,
+            on_reject: self.on_reject.clone(),
+            algorithm: PhantomData,
+        }
+    }
+}
+
+/// The [`Service`] produced by [`VerifyDigestLayer`].
+#[derive(Clone, Debug)]
+pub struct VerifyDigestService<S, A: Algorithm, R = DefaultOnReject> {
+    inner: S,
+    mode: VerifyMode,
+    multiple_values: MultipleValues,
+    body_limit: usize,
+    on_reject: R,
+    algorithm: PhantomData<A>,
+}
+
+/// The [`Service`] produced by [`ContentMd5Layer`].
+pub type ContentMd5Service<S, R = DefaultOnReject> = VerifyDigestService<S, Md5, R>;
+
+impl<S, A, R, ReqBody, ResBody> Service<Request<ReqBody>> for VerifyDigestService<S, A, R>
+where
+    S: Service<Request<Full<Bytes>>, Response = Response<ResBody>> + Clone + Send + 'static,
+    S::Future: Send,
+    A: Compute,
+    R: OnReject<ResBody, A> + Clone + Send + 'static,
+    ReqBody: Body + Send + 'static,
+    ReqBody::Data: Send,
+    ReqBody::Error: Into<BoxError>,
+{
+    type Response = Response<ResBody>;
+    type Error = S::Error;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<ReqBody>) -> Self::Future {
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let mode = self.mode;
+        let multiple_values = self.multiple_values;
+        let body_limit = self.body_limit;
+        let on_reject = self.on_reject.clone();
+
+        Box::pin(async move {
+            let values = request.headers().get_all(DigestHeader::<A>::name());
+            let expected = if mode == VerifyMode::Ignore {
+                None
+            } else {
+                match DigestHeader::<A>::decode_with(values, multiple_values) {
+                    Ok(expected) => Some(expected),
+                    Err(DigestError::Missing) if mode == VerifyMode::Required => {
+                        return Ok(on_reject.on_reject(DigestRejection::Missing));
+                    }
+                    Err(DigestError::Missing) => None,
+                    Err(err) => return Ok(on_reject.on_reject(DigestRejection::Malformed(err))),
+                }
+            };
+
+            let (parts, body) = request.into_parts();
+            // Only bodies that are verified are bounded, so unverified uploads are not rejected.
+            let limit = if expected.is_some() {
+                body_limit
+            } else {
+                usize::MAX
+            };
+            let bytes = match Limited::new(body, limit).collect().await {
+                Ok(collected) => collected.to_bytes(),
+                Err(err) if err.is::<LengthLimitError>() => {
+                    let rejection = DigestRejection::TooLarge { limit: body_limit };
+                    return Ok(on_reject.on_reject(rejection));
+                }
+                Err(err) => return Ok(on_reject.on_reject(DigestRejection::Body(err))),
+            };
+
+            if let Some(Err(mismatch)) = expected.map(|expected| expected.verify(&bytes)) {
+                return Ok(on_reject.on_reject(DigestRejection::Mismatch(mismatch)));
+            }
+
+            inner
+                .call(Request::from_parts(parts, Full::new(bytes)))
+                .await
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ContentMd5, ContentMd5Layer, ContentMd5Mode, ContentMd5Rejection};
+    use bytes::Bytes;
+    use headers::HeaderMapExt;
+    use http::{HeaderValue, Request, Response, StatusCode};
+    use http_body_util::{BodyExt, Full};
+    use std::convert::Infallible;
+    use tower::{service_fn, Layer, ServiceExt};
+
+    async fn echo(request: Request<Full<Bytes>>) -> Result<Response<Full<Bytes>>, Infallible> {
+        let body = request.into_body().collect().await.unwrap().to_bytes();
+        Ok(Response::new(Full::new(body)))
+    }
+
+    fn request(body: &'static str, md5: Option<HeaderValue>) -> Request<Full<Bytes>> {
+        let mut request = Request::new(Full::new(Bytes::from_static(body.as_bytes())));
+        if let Some(md5) = md5 {
+            request.headers_mut().insert("content-md5", md5);
+        }
+        request
+    }
+
+    #[tokio::test]
+    async fn passes_matching_request() {
+        let mut request = request("Check Integrity!", None);
+        request
+            .headers_mut()
+            .typed_insert(ContentMd5::compute(b"Check Integrity!"));
+
+        let service = ContentMd5Layer::required().layer(service_fn(echo));
+        let response = service.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(body, "Check Integrity!");
+    }
+
+    #[tokio::test]
+    async fn rejects_by_mode() {
+        let cases = [
+            (ContentMd5Mode::Required, None, StatusCode::BAD_REQUEST),
+            (ContentMd5Mode::VerifyIfPresent, None, StatusCode::OK),
+            (
+                ContentMd5Mode::VerifyIfPresent,
+                Some(HeaderValue::from_static("not base64!")),
+                StatusCode::BAD_REQUEST,
+            ),
+            (
+                ContentMd5Mode::VerifyIfPresent,
+                Some(HeaderValue::from_static("1B2M2Y8AsgTpgAmY7PhCfg==")),
+                StatusCode::BAD_REQUEST,
+            ),
+            (
+                ContentMd5Mode::Ignore,
+                Some(HeaderValue::from_static("1B2M2Y8AsgTpgAmY7PhCfg==")),
+                StatusCode::OK,
+            ),
+        ];
+
+        for (mode, md5, status) in cases {
+            let service = ContentMd5Layer::new(mode).layer(service_fn(echo));
+            let response = service
+                .oneshot(request("Check Integrity!", md5))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), status, "{mode:?}");
+        }
+    }
+
+    #[tokio::test]
+    async fn limits_verified_body() {
+        let md5 = ContentMd5::compute(b"Check Integrity!").to_string();
+        let md5 = HeaderValue::from_str(&md5).unwrap();
+        let cases = [
+            (
+                ContentMd5Mode::VerifyIfPresent,
+                8,
+                Some(md5.clone()),
+                StatusCode::PAYLOAD_TOO_LARGE,
+            ),
+            (ContentMd5Mode::VerifyIfPresent, 8, None, StatusCode::OK),
+            (ContentMd5Mode::Ignore, 8, Some(md5.clone()), StatusCode::OK),
+            (ContentMd5Mode::Required, 16, Some(md5), StatusCode::OK),
+        ];
+
+        for (mode, limit, md5, status) in cases {
+            let service = ContentMd5Layer::new(mode)
+                .body_limit(limit)
+                .layer(service_fn(echo));
+            let response = service
+                .oneshot(request("Check Integrity!", md5))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), status, "{mode:?}");
+            if status == StatusCode::OK {
+                let body = response.into_body().collect().await.unwrap().to_bytes();
+                assert_eq!(body, "Check Integrity!");
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn uses_custom_rejection() {
+        let layer = ContentMd5Layer::verify_if_present().on_reject(|rejection| {
+            let status = match rejection {
+                ContentMd5Rejection::Mismatch(_) => StatusCode::UNPROCESSABLE_ENTITY,
+                _ => StatusCode::BAD_REQUEST,
+            };
+            let mut response = Response::new(Full::new(Bytes::from(rejection.to_string())));
+            *response.status_mut() = status;
+            response
+        });
+
+        let md5 = HeaderValue::from_static("1B2M2Y8AsgTpgAmY7PhCfg==");
+        let response = layer
+            .layer(service_fn(echo))
+            .oneshot(request("Check Integrity!", Some(md5)))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+}
@@ -0,0 +1,452 @@
+use super::{BoxFuture, DEFAULT_BODY_LIMIT};
+use crate::algorithm::{Algorithm, Compute, Md5};
+use crate::trailers::announce;
+use crate::{accepts_trailers, ComputeDigestBody, DigestHasher, DigestHeader, DigestPlacement};
+use headers::{Header, HeaderMapExt};
+use http::{Method, Request, Response, StatusCode};
+use http_body::{Body, Frame, SizeHint};
+use http_body_util::BodyExt;
+use pin_project_lite::pin_project;
+use std::collections::VecDeque;
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// A [`Layer`] that adds a [`DigestHeader`] to outgoing responses.
+///
+/// Bodies without a known exact size are streamed and the digest is sent as a trailer through
+/// [`ComputeDigestBody`] if [`DigestHeader::announce_trailer`] permits it, announcing the
+/// trailer in the `Trailer` header. Otherwise the body is buffered up to the
+/// [buffer limit](Self::buffer_limit) and the digest is inserted into the header block; bodies
+/// that turn out to be larger, or whose exact size is known to exceed the limit, are passed
+/// through without a digest. Responses that already carry the header, `204 No Content` and
+/// `304 Not Modified` responses, and responses to `HEAD` requests are passed through
+/// unchanged.
+///
+/// # Example
+///
+/// ```
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// use bytes::Bytes;
+/// use headers::HeaderMapExt;
+/// use http::{Request, Response};
+/// use http_body_util::Full;
+/// use headers_content_md5::{ContentMd5, SetContentMd5Layer};
+/// use std::convert::Infallible;
+/// use tower::{service_fn, Layer, ServiceExt};
+///
+/// async fn handler(_: Request<()>) -> Result<Response<Full<Bytes>>, Infallible> {
+///     Ok(Response::new(Full::new(Bytes::from("Check Integrity!"))))
+/// }
+///
+/// let service = SetContentMd5Layer::new().layer(service_fn(handler));
+///
+/// let response = service.oneshot(Request::new(())).await.unwrap();
+/// assert_eq!(
+///     response.headers().typed_get::<ContentMd5>(),
+///     Some(ContentMd5::compute(b"Check Integrity!"))
+/// );
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct SetDigestLayer<A: Algorithm> {
+    buffer_limit: usize,
+    algorithm: PhantomData<A>,
+}
+
+/// A [`SetDigestLayer`] adding a `Content-MD5` to outgoing responses.
+pub type SetContentMd5Layer = SetDigestLayer<Md5>;
+
+impl<A: Algorithm> SetDigestLayer<A> {
+    /// Creates a new layer.
+    pub fn new() -> Self {
+        Self {
+            buffer_limit: DEFAULT_BODY_LIMIT,
+            algorithm: PhantomData,
+        }
+    }
+
+    /// Sets the maximum number of bytes buffered from a response body to place its digest in
+    /// the header block.
+    ///
+    /// Defaults to 2 MiB.
+    pub fn buffer_limit(mut self, limit: usize) -> Self {
+        self.buffer_limit = limit;
+        self
+    }
+}
+
+impl<A: Algorithm> Default for SetDigestLayer<A> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S, A: Algorithm> Layer<S> for SetDigestLayer<A> {
+    type Service = SetDigest<S, A>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        SetDigest {
+            inner,
+            buffer_limit: self.buffer_limit,
+            algorithm: PhantomData,
+        }
+    }
+}
+
+/// The [`Service`] produced by [`SetDigestLayer`].
+#[derive(Clone, Debug)]
+pub struct SetDigest<S, A: Algorithm> {
+    inner: S,
+    buffer_limit: usize,
+    algorithm: PhantomData<A>,
+}
+
+/// The [`Service`] produced by [`SetContentMd5Layer`].
+pub type SetContentMd5<S> = SetDigest<S, Md5>;
+
+impl<S, A, ReqBody, ResBody> Service<Request<ReqBody>> for SetDigest<S, A>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>>,
+    S::Future: Send + 'static,
+    A: Compute,
+    ResBody: Body + Send + 'static,
+    ResBody::Data: AsRef<[u8]> + Send,
+    ResBody::Error: Send,
+{
+    type Response = Response<SetDigestBody<ResBody, A>>;
+    type Error = S::Error;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<ReqBody>) -> Self::Future {
+        let is_head = request.method() == Method::HEAD;
+        let accepts_trailers = accepts_trailers(&request);
+        let version = request.version();
+        let buffer_limit = self.buffer_limit;
+        let future = self.inner.call(request);
+
+        Box::pin(async move {
+            let mut response = future.await?;
+            let skip = is_head
+                || response.status() == StatusCode::NO_CONTENT
+                || response.status() == StatusCode::NOT_MODIFIED
+                || response.headers().contains_key(DigestHeader::<A>::name());
+            if skip {
+                return Ok(response.map(SetDigestBody::unchanged));
+            }
+
+            let exact = response.body().size_hint().exact();
+            let too_large = exact.is_some_and(|len| len > buffer_limit as u64);
+            if (exact.is_none() || too_large)
+                && announce::<A, _>(&mut response, accepts_trailers, version)
+                    == DigestPlacement::Trailer
+            {
+                return Ok(response.map(SetDigestBody::streaming));
+            }
+            if too_large {
+                return Ok(response.map(SetDigestBody::unchanged));
+            }
+
+            let (mut parts, body) = response.into_parts();
+            let mut body = Box::pin(body);
+            let mut hasher = DigestHasher::<A>::new();
+            let mut frames = VecDeque::new();
+            let mut buffered = 0;
+            while let Some(frame) = body.frame().await {
+                let failed = frame.is_err();
+                if let Some(data) = frame.as_ref().ok().and_then(Frame::data_ref) {
+                    hasher.update(data);
+                    buffered += data.as_ref().len();
+                }
+                frames.push_back(frame);
+
+                // Replay the error to the consumer instead of stamping a partial digest.
+                if failed {
+                    return Ok(Response::from_parts(parts, SetDigestBody::buffered(frames)));
+                }
+                // Give up on bodies outgrowing the limit, replaying what was read so far.
+                if buffered > buffer_limit {
+                    let body = SetDigestBody::replaying(frames, body);
+                    return Ok(Response::from_parts(parts, body));
+                }
+            }
+
+            parts.headers.typed_insert(hasher.finalize());
+            Ok(Response::from_parts(parts, SetDigestBody::buffered(frames)))
+        })
+    }
+}
+
+pin_project! {
+    /// The response body produced by [`SetDigest`].
+    pub struct SetDigestBody<B, A>
+    where
+        B: Body,
+        A: Compute,
+    {
+        #[pin]
+        kind: Kind<B, A>,
+    }
+}
+
+/// The response body produced by [`SetContentMd5`].
+pub type SetContentMd5Body<B> = SetDigestBody<B, Md5>;
+
+pin_project! {
+    #[project = KindProj]
+    enum Kind<B, A>
+    where
+        B: Body,
+        A: Compute,
+    {
+        Unchanged { #[pin] body: B },
+        Streaming { #[pin] body: ComputeDigestBody<B, A> },
+        Buffered { frames: VecDeque<Result<Frame<B::Data>, B::Error>> },
+        Replaying {
+            frames: VecDeque<Result<Frame<B::Data>, B::Error>>,
+            body: Pin<Box<B>>,
+        },
+    }
+}
+
+impl<B: Body, A: Compute> SetDigestBody<B, A> {
+    fn unchanged(body: B) -> Self {
+        Self {
+            kind: Kind::Unchanged { body },
+        }
+    }
+
+    fn streaming(body: B) -> Self {
+        Self {
+            kind: Kind::Streaming {
+                body: ComputeDigestBody::new(body),
+            },
+        }
+    }
+
+    fn buffered(frames: VecDeque<Result<Frame<B::Data>, B::Error>>) -> Self {
+        Self {
+            kind: Kind::Buffered { frames },
+        }
+    }
+
+    fn replaying(frames: VecDeque<Result<Frame<B::Data>, B::Error>>, body: Pin<Box<B>>) -> Self {
+        Self {
+            kind: Kind::Replaying { frames, body },
+        }
+    }
+}
+
+/// Returns the number of data bytes in buffered `frames`.
+fn buffered_len<D: AsRef<[u8]>, E>(frames: &VecDeque<Result<Frame<D>, E>>) -> u64 {
+    frames
+        .iter()
+        .filter_map(|frame| frame.as_ref().ok()?.data_ref())
+        .map(|data| data.as_ref().len() as u64)
+        .sum()
+}
+
+impl<B, A> Body for SetDigestBody<B, A>
+where
+    B: Body,
+    B::Data: AsRef<[u8]>,
+    A: Compute,
+{
+    type Data = B::Data;
+    type Error = B::Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        match self.project().kind.project() {
+            KindProj::Unchanged { body } => body.poll_frame(cx),
+            KindProj::Streaming { body } => body.poll_frame(cx),
+            KindProj::Buffered { frames } => Poll::Ready(frames.pop_front()),
+            KindProj::Replaying { frames, body } => match frames.pop_front() {
+                Some(frame) => Poll::Ready(Some(frame)),
+                None => body.as_mut().poll_frame(cx),
+            },
+        }
+    }
+
+    fn is_end_stream(&self) -> bool {
+        match &self.kind {
+            Kind::Unchanged { body } => body.is_end_stream(),
+            Kind::Streaming { body } => body.is_end_stream(),
+            Kind::Buffered { frames } => frames.is_empty(),
+            Kind::Replaying { frames, body } => frames.is_empty() && body.is_end_stream(),
+        }
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        match &self.kind {
+            Kind::Unchanged { body } => body.size_hint(),
+            Kind::Streaming { body } => body.size_hint(),
+            Kind::Buffered { frames } => SizeHint::with_exact(buffered_len(frames)),
+            Kind::Replaying { frames, body } => {
+                let len = buffered_len(frames);
+                let rest = body.size_hint();
+                let mut hint = SizeHint::new();
+                if let Some(upper) = rest.upper() {
+                    hint.set_upper(upper + len);
+                }
+                hint.set_lower(rest.lower() + len);
+                hint
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ContentMd5, SetContentMd5Layer};
+    use bytes::Bytes;
+    use headers::HeaderMapExt;
+    use http::{Request, Response, StatusCode, Version};
+    use http_body::{Body, Frame};
+    use http_body_util::{BodyExt, Full, StreamBody};
+    use std::convert::Infallible;
+    use tower::{service_fn, Layer, ServiceExt};
+
+    #[tokio::test]
+    async fn sets_header_for_buffered_body() {
+        let service = SetContentMd5Layer::new().layer(service_fn(|_: Request<()>| async {
+            Ok::<_, Infallible>(Response::new(Full::new(Bytes::from("Check Integrity!"))))
+        }));
+
+        let response = service.oneshot(Request::new(())).await.unwrap();
+        assert_eq!(
+            response.headers().typed_get::<ContentMd5>(),
+            Some(ContentMd5::compute(b"Check Integrity!"))
+        );
+        let collected = response.into_body().collect().await.unwrap();
+        assert!(collected.trailers().is_none());
+        assert_eq!(collected.to_bytes(), "Check Integrity!");
+    }
+
+    #[tokio::test]
+    async fn sets_trailer_for_streaming_body() {
+        let service = SetContentMd5Layer::new().layer(service_fn(|_: Request<()>| async {
+            let frames = ["Check ", "Integrity!"].map(|chunk| {
+                Ok::<_, Infallible>(Frame::data(Bytes::from_static(chunk.as_bytes())))
+            });
+            let body = StreamBody::new(futures_util::stream::iter(frames));
+            Ok::<_, Infallible>(Response::new(body))
+        }));
+
+        let request = Request::get("/").header("te", "trailers").body(()).unwrap();
+        let response = service.clone().oneshot(request).await.unwrap();
+        assert!(response.headers().typed_get::<ContentMd5>().is_none());
+        assert_eq!(response.headers()["trailer"], "content-md5");
+        let collected = response.into_body().collect().await.unwrap();
+        assert_eq!(
+            collected.trailers().unwrap().typed_get::<ContentMd5>(),
+            Some(ContentMd5::compute(b"Check Integrity!"))
+        );
+
+        // Without `TE: trailers` the body is buffered instead.
+        let response = service.oneshot(Request::new(())).await.unwrap();
+        assert!(!response.headers().contains_key("trailer"));
+        assert_eq!(
+            response.headers().typed_get::<ContentMd5>(),
+            Some(ContentMd5::compute(b"Check Integrity!"))
+        );
+        let collected = response.into_body().collect().await.unwrap();
+        assert!(collected.trailers().is_none());
+        assert_eq!(collected.to_bytes(), "Check Integrity!");
+    }
+
+    #[tokio::test]
+    async fn passes_through_endless_body() {
+        let service =
+            SetContentMd5Layer::new()
+                .buffer_limit(64)
+                .layer(service_fn(|_: Request<()>| async {
+                    let frames = futures_util::stream::repeat_with(|| {
+                        Ok::<_, Infallible>(Frame::data(Bytes::from_static(b"Check Integrity!")))
+                    });
+                    Ok::<_, Infallible>(Response::new(StreamBody::new(frames)))
+                }));
+
+        let response = service.oneshot(Request::new(())).await.unwrap();
+        assert!(response.headers().typed_get::<ContentMd5>().is_none());
+        let mut body = response.into_body();
+        assert!(!body.is_end_stream());
+        for _ in 0..8 {
+            let frame = body.frame().await.unwrap().unwrap();
+            assert_eq!(frame.into_data().unwrap(), "Check Integrity!");
+        }
+    }
+
+    #[tokio::test]
+    async fn does_not_buffer_large_body() {
+        let service =
+            SetContentMd5Layer::new()
+                .buffer_limit(8)
+                .layer(service_fn(|_: Request<()>| async {
+                    Ok::<_, Infallible>(Response::new(Full::new(Bytes::from("Check Integrity!"))))
+                }));
+
+        let response = service.clone().oneshot(Request::new(())).await.unwrap();
+        assert!(response.headers().typed_get::<ContentMd5>().is_none());
+        let collected = response.into_body().collect().await.unwrap();
+        assert!(collected.trailers().is_none());
+        assert_eq!(collected.to_bytes(), "Check Integrity!");
+
+        let request = Request::get("/")
+            .version(Version::HTTP_2)
+            .header("te", "trailers")
+            .body(())
+            .unwrap();
+        let response = service.oneshot(request).await.unwrap();
+        assert!(response.headers().typed_get::<ContentMd5>().is_none());
+        let collected = response.into_body().collect().await.unwrap();
+        assert_eq!(
+            collected.trailers().unwrap().typed_get::<ContentMd5>(),
+            Some(ContentMd5::compute(b"Check Integrity!"))
+        );
+    }
+
+    #[tokio::test]
+    async fn skips_exempt_responses() {
+        let service =
+            SetContentMd5Layer::new().layer(service_fn(|request: Request<()>| async move {
+                let mut response = Response::new(Full::new(Bytes::from("Check Integrity!")));
+                match request.uri().path() {
+                    "/no-content" => *response.status_mut() = StatusCode::NO_CONTENT,
+                    "/not-modified" => *response.status_mut() = StatusCode::NOT_MODIFIED,
+                    "/stamped" => response.headers_mut().typed_insert(ContentMd5([0; 16])),
+                    _ => {}
+                }
+                Ok::<_, Infallible>(response)
+            }));
+
+        for path in ["/no-content", "/not-modified"] {
+            let request = Request::get(path).body(()).unwrap();
+            let response = service.clone().oneshot(request).await.unwrap();
+            assert!(
+                response.headers().typed_get::<ContentMd5>().is_none(),
+                "{path}"
+            );
+        }
+
+        let request = Request::head("/").body(()).unwrap();
+        let response = service.clone().oneshot(request).await.unwrap();
+        assert!(response.headers().typed_get::<ContentMd5>().is_none());
+
+        let request = Request::get("/stamped").body(()).unwrap();
+        let response = service.oneshot(request).await.unwrap();
+        assert_eq!(
+            response.headers().typed_get::<ContentMd5>(),
+            Some(ContentMd5([0; 16]))
+        );
+    }
+}
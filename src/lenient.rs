@@ -0,0 +1,153 @@
+//! Lenient decoding of digest header values sent by non-conforming clients.
+
+use crate::algorithm::{Algorithm, MAX_DIGEST_LEN};
+use crate::convert::decode_hex;
+use crate::{DigestError, DigestHeader};
+use base64::alphabet;
+use base64::engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig};
+use base64::Engine;
+use headers::HeaderValue;
+
+const LENIENT_CONFIG: GeneralPurposeConfig =
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent);
+const LENIENT_STANDARD: GeneralPurpose = GeneralPurpose::new(&alphabet::STANDARD, LENIENT_CONFIG);
+const LENIENT_URL_SAFE: GeneralPurpose = GeneralPurpose::new(&alphabet::URL_SAFE, LENIENT_CONFIG);
+
+/// The normalizations [`DigestHeader::decode_lenient`] applied to accept a value.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Normalization {
+    /// Leading or trailing whitespace was removed.
+    pub trimmed_whitespace: bool,
+    /// The value used the URL-safe base64 alphabet.
+    pub url_safe_alphabet: bool,
+    /// The value lacked its base64 padding.
+    pub missing_padding: bool,
+    /// The value was a hex digest rather than base64.
+    pub hex: bool,
+}
+
+impl Normalization {
+    /// Returns `true` if the value was accepted as-is.
+    pub fn is_canonical(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl<A: Algorithm> DigestHeader<A> {
+    /// Decodes a header value, accepting common deviations from RFC 1864.
+    ///
+    /// In addition to the canonical form accepted by [`Header::decode`](headers::Header::decode),
+    /// this accepts surrounding whitespace, the URL-safe base64 alphabet, missing padding and
+    /// hex digests. The returned [`Normalization`] reports which of these were encountered.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use http::HeaderValue;
+    /// use headers_content_md5::ContentMd5;
+    ///
+    /// let value = HeaderValue::from_static(" 436865636b20496e7465677269747921 ");
+    /// let (md5, normalization) = ContentMd5::decode_lenient(&value).unwrap();
+    /// assert_eq!(md5.0, "Check Integrity!".as_bytes());
+    /// assert!(normalization.trimmed_whitespace);
+    /// assert!(normalization.hex);
+    /// ```
+    pub fn decode_lenient(value: &HeaderValue) -> Result<(Self, Normalization), DigestError> {
+        let raw = value.to_str().map_err(|_| DigestError::NotAscii)?;
+        let value = raw.trim();
+        let mut normalization = Normalization {
+            trimmed_whitespace: value.len() != raw.len(),
+            ..Normalization::default()
+        };
+
+        if let Some(digest) = decode_hex(value) {
+            normalization.hex = true;
+            return Ok((digest, normalization));
+        }
+
+        let unpadded_len = (A::DIGEST_LEN * 4).div_ceil(3);
+        normalization.url_safe_alphabet = value.contains(['-', '_']);
+        normalization.missing_padding = value.len() == unpadded_len;
+        if value.len() != unpadded_len && value.len() != Self::BASE64_LEN {
+            return Err(DigestError::InvalidLength { len: value.len() });
+        }
+
+        let engine = if normalization.url_safe_alphabet {
+            &LENIENT_URL_SAFE
+        } else {
+            &LENIENT_STANDARD
+        };
+        let mut buffer = [0; MAX_DIGEST_LEN + 2];
+        let len = engine
+            .decode_slice(value, &mut buffer)
+            .map_err(|_| DigestError::InvalidBase64)?;
+        Ok((Self::try_from(&buffer[..len])?, normalization))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ContentMd5, Normalization};
+    use http::HeaderValue;
+
+    const DIGEST: [u8; 16] = [
+        0xfb, 0xff, 0xbf, 0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb,
+        0xcc,
+    ];
+
+    #[test]
+    fn accepts_canonical_value() {
+        let value = HeaderValue::from_static("+/+/ABEiM0RVZneImaq7zA==");
+        let (md5, normalization) = ContentMd5::decode_lenient(&value).unwrap();
+        assert_eq!(md5, ContentMd5(DIGEST));
+        assert!(normalization.is_canonical());
+    }
+
+    #[test]
+    fn normalizes_variants() {
+        let cases = [
+            (
+                "\t+/+/ABEiM0RVZneImaq7zA== ",
+                Normalization {
+                    trimmed_whitespace: true,
+                    ..Normalization::default()
+                },
+            ),
+            (
+                "-_-_ABEiM0RVZneImaq7zA",
+                Normalization {
+                    url_safe_alphabet: true,
+                    missing_padding: true,
+                    ..Normalization::default()
+                },
+            ),
+            (
+                "FBFFBF00112233445566778899aabbcc",
+                Normalization {
+                    hex: true,
+                    ..Normalization::default()
+                },
+            ),
+        ];
+
+        for (value, expected) in cases {
+            let value = HeaderValue::from_static(value);
+            let (md5, normalization) = ContentMd5::decode_lenient(&value).unwrap();
+            assert_eq!(md5, ContentMd5(DIGEST), "{value:?}");
+            assert_eq!(normalization, expected, "{value:?}");
+        }
+    }
+
+    #[test]
+    fn rejects_garbage() {
+        for value in [
+            "",
+            "not a digest",
+            "+/+/ABEiM0RVZneImaq7zA=",
+            "+/+/ABEiM0RVZneImaq7zAAA",
+        ] {
+            let value = HeaderValue::from_static(value);
+            assert!(ContentMd5::decode_lenient(&value).is_err(), "{value:?}");
+        }
+    }
+}
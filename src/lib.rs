@@ -1,4 +1,18 @@
-//! Provides the [`ContentMd5`] typed header.
+//! Provides the [`ContentMd5`](type@ContentMd5) typed header, and the generic [`DigestHeader`]
+//! it is built on.
+//!
+//! The [RFC 9530](https://datatracker.ietf.org/doc/html/rfc9530) [`ContentDigest`] and
+//! [`ReprDigest`] fields are supported as well, and expose an embedded MD5 digest as a
+//! [`ContentMd5`](type@ContentMd5). [`WantContentDigest`] and [`WantReprDigest`] negotiate
+//! which digest to send. The legacy
+//! [RFC 3230](https://datatracker.ietf.org/doc/html/rfc3230) [`Digest`] and [`WantDigest`]
+//! fields are supported for older clients. [`ExpectedDigests`] collects the digests announced
+//! by all of these headers at once.
+//!
+//! Digests also convert to and from strong [`ETag`](headers::ETag)s and evaluate `If-Match` and
+//! `If-None-Match` preconditions, and [`S3Etag`] derives Amazon S3-compatible entity tags from
+//! MD5 digests. The [`GoogHash`], [`MsBlobContentMd5`] and [`MsContentMd5`] headers carry MD5
+//! digests in the spelling of cloud storage services.
 //!
 //! # Example
 //!
@@ -11,12 +25,111 @@
 //! let md5 = ContentMd5::decode(&mut [&value].into_iter()).unwrap();
 //! assert_eq!(md5.0, "Check Integrity!".as_bytes())
 //! ```
+//!
+//! # Crate features
+//!
+//! * `md5` - Enables `DigestHeader::compute`, `DigestHeader::verify` and the incremental
+//!   `DigestHasher` for MD5.
+//! * `sha1` - Enables computing SHA-1 digests.
+//! * `sha2` - Enables computing SHA-256 and SHA-512 digests.
+//! * `body` - Enables the `VerifyDigestBody`, `VerifyDigestTrailer` and `ComputeDigestBody`
+//!   body adapters for `http_body` 1.0 bodies; implies `md5`.
+//! * `tower` - Enables the `VerifyDigestLayer` middleware verifying request bodies and the
+//!   `SetDigestLayer` middleware adding digests to responses; implies `body`.
+//! * `axum` - Enables the `VerifiedDigestBody` extractor for `axum`; implies `md5`.
+//! * `serde` - Implements `Serialize` and `Deserialize` for [`DigestHeader`]; see the `serde`
+//!   module.
 
 #![deny(unsafe_code)]
 #![deny(unused_must_use)]
 
-use base64::{engine::general_purpose::STANDARD as base64, Engine};
 use headers::{Header, HeaderValue};
+use subtle::ConstantTimeEq;
+
+pub mod algorithm;
+#[cfg(feature = "body")]
+mod body;
+mod convert;
+mod dialects;
+mod digest_fields;
+mod error;
+mod etag;
+#[cfg(feature = "axum")]
+mod extract;
+#[cfg(any(feature = "md5", feature = "sha1", feature = "sha2"))]
+mod hasher;
+mod instance_digest;
+mod integrity;
+#[cfg(feature = "tower")]
+pub mod layer;
+mod lenient;
+mod negotiate;
+pub mod policy;
+mod s3;
+#[cfg(feature = "serde")]
+pub mod serde;
+mod structured;
+mod trailers;
+#[cfg(any(feature = "md5", feature = "sha1", feature = "sha2"))]
+mod verify;
+
+pub use algorithm::{Algorithm, Compute, Md5};
+#[cfg(feature = "body")]
+pub use body::{
+    ComputeContentMd5, ComputeDigestBody, VerifyBodyError, VerifyContentMd5,
+    VerifyContentMd5Trailer, VerifyDigestBody, VerifyDigestTrailer,
+};
+pub use dialects::{GoogHash, MsBlobContentMd5, MsContentMd5};
+pub use digest_fields::{ContentDigest, ReprDigest};
+pub use error::{ContentMd5Error, DigestError};
+pub use etag::EtagEncoding;
+#[cfg(feature = "axum")]
+pub use extract::{
+    VerifiedDigestBody, VerifiedDigestBodyRejection, VerifiedMd5Body, VerifiedMd5BodyRejection,
+};
+#[cfg(feature = "md5")]
+pub use hasher::ContentMd5Hasher;
+#[cfg(any(feature = "md5", feature = "sha1", feature = "sha2"))]
+pub use hasher::DigestHasher;
+pub use instance_digest::{Digest, WantDigest};
+pub use integrity::{DigestSource, ExpectedDigests, IntegrityError};
+#[cfg(feature = "tower")]
+pub use layer::{
+    ContentMd5Layer, ContentMd5Mode, ContentMd5Rejection, DigestRejection, SetContentMd5Layer,
+    SetDigestLayer, VerifyDigestLayer, VerifyMode,
+};
+pub use lenient::Normalization;
+pub use negotiate::{WantContentDigest, WantReprDigest, MAX_WEIGHT};
+pub use policy::{ContentMd5With, DigestHeaderWith, MultipleValues};
+pub use s3::S3Etag;
+pub use trailers::{accepts_trailers, DigestPlacement};
+#[cfg(any(feature = "md5", feature = "sha1", feature = "sha2"))]
+pub use verify::{DigestMismatch, Md5Mismatch};
+
+/// A typed header carrying a single base64-encoded digest of the message body.
+///
+/// The [`Algorithm`] `A` selects the digest length and the header name. Decoding and
+/// encoding follow the rules of `Content-MD5`: only the canonical, padded base64 encoding of
+/// a digest of the expected length is accepted, and multiple values must agree.
+///
+/// # Example
+///
+/// ```
+/// use headers::HeaderMapExt;
+/// use http::{HeaderMap, HeaderValue};
+/// use headers_content_md5::{algorithm::Sha256, DigestHeader};
+///
+/// let mut headers = HeaderMap::new();
+/// headers.insert(
+///     "content-sha256",
+///     HeaderValue::from_static("47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU="),
+/// );
+///
+/// let sha256 = headers.typed_get::<DigestHeader<Sha256>>().unwrap();
+/// assert_eq!(sha256.to_hex().len(), 64);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct DigestHeader<A: Algorithm>(pub A::Digest);
 
 /// `Content-MD5` header, defined in
 /// [RFC1864](https://datatracker.ietf.org/doc/html/rfc1864)
@@ -25,6 +138,13 @@ use headers::{Header, HeaderValue};
 ///
 /// * `Q2hlY2sgSW50ZWdyaXR5IQ==`
 ///
+/// Decoding is strict: only the canonical 24-character base64 encoding of a 16-byte
+/// digest is accepted, with `==` padding and zero trailing bits. Use
+/// [`DigestHeader::decode_lenient`] to accept values from non-conforming clients.
+///
+/// Multiple values are only accepted if they all decode to the same digest; see
+/// [`ContentMd5With`] for other [`policy`] choices.
+///
 /// # Example
 ///
 /// Decoding:
@@ -55,47 +175,84 @@ use headers::{Header, HeaderValue};
 /// md5.encode(&mut header);
 /// assert_eq!(header[0], "Q2hlY2sgSW50ZWdyaXR5IQ==");
 /// ```
-#[derive(Clone, Copy, Debug, PartialEq)]
-pub struct ContentMd5(pub [u8; 16]);
+pub type ContentMd5 = DigestHeader<Md5>;
 
-static CONTENT_MD5: http::header::HeaderName = http::header::HeaderName::from_static("content-md5");
+/// Creates a [`ContentMd5`](type@ContentMd5) from a raw digest, keeping the call syntax of the
+/// former tuple struct working.
+#[allow(non_snake_case)]
+pub const fn ContentMd5(digest: [u8; 16]) -> ContentMd5 {
+    DigestHeader(digest)
+}
+
+impl<A: Algorithm> DigestHeader<A> {
+    /// Wraps a raw digest.
+    pub const fn new(digest: A::Digest) -> Self {
+        Self(digest)
+    }
 
-impl Header for ContentMd5 {
+    /// Decodes a single header value, reporting why it was rejected.
+    ///
+    /// This applies the same strict validation as [`Header::decode`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use http::HeaderValue;
+    /// use headers_content_md5::{ContentMd5, DigestError};
+    ///
+    /// let value = HeaderValue::from_static("Q2hlY2sgSW50ZWdyaXR5IQ==");
+    /// assert_eq!(ContentMd5::parse(&value).unwrap().0, "Check Integrity!".as_bytes());
+    ///
+    /// let value = HeaderValue::from_static("Q2hlY2sgSW50ZWdyaXR5IQ");
+    /// assert_eq!(
+    ///     ContentMd5::parse(&value),
+    ///     Err(DigestError::InvalidLength { len: 22 })
+    /// );
+    /// ```
+    pub fn parse(value: &HeaderValue) -> Result<Self, DigestError> {
+        let value = value.to_str().map_err(|_| DigestError::NotAscii)?;
+        Self::from_base64(value)
+    }
+
+    /// Compares two digests in constant time.
+    ///
+    /// Unlike `==`, the comparison does not exit early on the first differing byte, so
+    /// the time taken does not reveal how much of a digest matched.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use headers_content_md5::ContentMd5;
+    ///
+    /// let md5 = ContentMd5(*b"Check Integrity!");
+    /// assert!(md5.ct_eq(&ContentMd5(*b"Check Integrity!")));
+    /// assert!(!md5.ct_eq(&ContentMd5(*b"Check Integrity?")));
+    /// ```
+    pub fn ct_eq(&self, other: &Self) -> bool {
+        self.0.as_ref().ct_eq(other.0.as_ref()).into()
+    }
+}
+
+impl<A: Algorithm> Header for DigestHeader<A> {
     fn name() -> &'static http::header::HeaderName {
-        &CONTENT_MD5
+        A::header_name()
     }
 
     fn decode<'i, I: Iterator<Item = &'i HeaderValue>>(
         values: &mut I,
     ) -> Result<Self, headers::Error> {
-        let value = values.next().ok_or_else(headers::Error::invalid)?;
-
-        // Ensure base64 encoded length fits the expected MD5 digest length.
-        if value.len() < 22 || value.len() > 24 {
-            return Err(headers::Error::invalid());
-        }
-
-        let value = value.to_str().map_err(|_| headers::Error::invalid())?;
-        let mut buffer = [0; 18];
-        base64
-            .decode_slice(value, &mut buffer)
-            .map_err(|_| headers::Error::invalid())?;
-        let mut slice = [0; 16];
-        slice.copy_from_slice(&buffer[..16]);
-        Ok(Self(slice))
+        Ok(Self::decode_with(values, MultipleValues::default())?)
     }
 
     fn encode<E: Extend<HeaderValue>>(&self, values: &mut E) {
-        let encoded = base64.encode(self.0);
-        if let Ok(value) = HeaderValue::from_str(&encoded) {
-            values.extend(std::iter::once(value));
-        }
+        values.extend(std::iter::once(self.to_header_value()));
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::ContentMd5;
+    use crate::algorithm::{Sha1, Sha256};
+    use crate::{ContentMd5, ContentMd5Error, DigestHeader};
     use headers::Header;
     use http::HeaderValue;
 
@@ -106,6 +263,68 @@ mod tests {
         assert_eq!(md5.0, "Check Integrity!".as_bytes())
     }
 
+    #[test]
+    fn decode_rejects_non_canonical_values() {
+        for value in [
+            // 17 and 18 decoded bytes
+            "Q2hlY2sgSW50ZWdyaXR5IT8=",
+            "Q2hlY2sgSW50ZWdyaXR5IT8/",
+            // non-zero trailing bits
+            "Q2hlY2sgSW50ZWdyaXR5IR==",
+            // missing padding
+            "Q2hlY2sgSW50ZWdyaXR5IQ",
+            // URL-safe alphabet
+            "-_-_ABEiM0RVZneImaq7zA==",
+        ] {
+            let value = HeaderValue::from_static(value);
+            assert!(
+                ContentMd5::decode(&mut [&value].into_iter()).is_err(),
+                "{value:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn parse_reports_cause() {
+        let cases = [
+            (
+                "Q2hlY2sgSW50ZWdyaXR5IQ",
+                ContentMd5Error::InvalidLength { len: 22 },
+            ),
+            ("Q2hlY2sgSW50ZWdyaXR5I!==", ContentMd5Error::InvalidBase64),
+            (
+                "Q2hlY2sgSW50ZWdyaXR5IT8=",
+                ContentMd5Error::InvalidDigestLength { len: 17 },
+            ),
+        ];
+        for (value, expected) in cases {
+            let value = HeaderValue::from_static(value);
+            assert_eq!(ContentMd5::parse(&value), Err(expected), "{value:?}");
+        }
+
+        let value = HeaderValue::from_bytes(b"Q2hlY2sgSW50ZWdyaXR5I\xff==").unwrap();
+        assert_eq!(ContentMd5::parse(&value), Err(ContentMd5Error::NotAscii));
+    }
+
+    #[test]
+    fn decode_rejects_conflicting_values() {
+        let a = HeaderValue::from_static("Q2hlY2sgSW50ZWdyaXR5IQ==");
+        let b = HeaderValue::from_static("1B2M2Y8AsgTpgAmY7PhCfg==");
+        assert!(ContentMd5::decode(&mut [&a, &a].into_iter()).is_ok());
+        assert!(ContentMd5::decode(&mut [&a, &b].into_iter()).is_err());
+    }
+
+    #[test]
+    fn ct_eq_works() {
+        let md5 = ContentMd5(*b"Check Integrity!");
+        assert!(md5.ct_eq(&md5));
+        for i in 0..16 {
+            let mut other = md5;
+            other.0[i] ^= 0x80;
+            assert!(!md5.ct_eq(&other), "{i}");
+        }
+    }
+
     #[test]
     fn encode_works() {
         let md5 = ContentMd5("Check Integrity!".as_bytes().try_into().unwrap());
@@ -113,4 +332,21 @@ mod tests {
         md5.encode(&mut header);
         assert_eq!(header[0], "Q2hlY2sgSW50ZWdyaXR5IQ==");
     }
+
+    #[test]
+    fn other_algorithms_work() {
+        let value = HeaderValue::from_static("2jmj7l5rSw0yVb/vlWAYkK/YBwk=");
+        let sha1 = DigestHeader::<Sha1>::decode(&mut [&value].into_iter()).unwrap();
+        assert_eq!(sha1.to_hex(), "da39a3ee5e6b4b0d3255bfef95601890afd80709");
+        assert_eq!(DigestHeader::<Sha1>::name(), "content-sha1");
+
+        let mut header = Vec::default();
+        sha1.encode(&mut header);
+        assert_eq!(header[0], value);
+
+        assert_eq!(
+            DigestHeader::<Sha256>::parse(&value),
+            Err(ContentMd5Error::InvalidLength { len: 28 })
+        );
+    }
 }
@@ -0,0 +1,279 @@
+//! The `Want-Content-Digest` and `Want-Repr-Digest` fields of
+//! [RFC 9530](https://datatracker.ietf.org/doc/html/rfc9530#section-4), and digest negotiation.
+
+use crate::algorithm::Algorithm;
+use crate::structured::{self, BareItem};
+use crate::DigestError;
+use headers::{Header, HeaderValue};
+use http::header::HeaderName;
+use std::fmt;
+use std::str::FromStr;
+
+static WANT_CONTENT_DIGEST: HeaderName = HeaderName::from_static("want-content-digest");
+static WANT_REPR_DIGEST: HeaderName = HeaderName::from_static("want-repr-digest");
+
+/// The highest preference weight.
+pub const MAX_WEIGHT: u8 = 10;
+
+/// Parses a dictionary of weights into `preferences`, replacing weights of repeated algorithms.
+fn parse_preferences(value: &str, preferences: &mut Vec<(String, u8)>) -> Result<(), DigestError> {
+    let members = structured::parse_dictionary(value).ok_or(DigestError::InvalidDictionary)?;
+    for (algorithm, item) in members {
+        let weight = match item {
+            BareItem::Integer(weight @ 0..=10) => weight as u8,
+            _ => return Err(DigestError::InvalidDictionary),
+        };
+        insert_preference(preferences, algorithm, weight);
+    }
+    Ok(())
+}
+
+fn insert_preference(preferences: &mut Vec<(String, u8)>, algorithm: String, weight: u8) {
+    match preferences.iter_mut().find(|(name, _)| *name == algorithm) {
+        Some(preference) => preference.1 = weight,
+        None => preferences.push((algorithm, weight)),
+    }
+}
+
+/// Picks the algorithm to emit from `supported`, given the client's `preferences`, in which a
+/// weight equal to the default of `W` marks an algorithm as not acceptable.
+pub(crate) fn negotiate<'a, W: Copy + Default + Ord>(
+    preferences: &[(String, W)],
+    supported: &[&'a str],
+) -> Option<&'a str> {
+    let mut best: Option<(&'a str, W)> = None;
+    for &algorithm in supported {
+        let weight = preferences
+            .iter()
+            .find(|(name, _)| name == algorithm)
+            .map_or(W::default(), |(_, weight)| *weight);
+        // Earlier entries of `supported` win ties.
+        if weight > W::default() && best.is_none_or(|(_, best)| weight > best) {
+            best = Some((algorithm, weight));
+        }
+    }
+    best.map(|(algorithm, _)| algorithm)
+}
+
+/// Generates a typed header holding algorithm preferences.
+macro_rules! want_field {
+    ($(#[$meta:meta])* $field:ident, $name:ident) => {
+        $(#[$meta])*
+        #[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+        pub struct $field {
+            preferences: Vec<(String, u8)>,
+        }
+
+        impl $field {
+            /// Creates a field without any preferences.
+            ///
+            /// Like an empty [`ContentDigest`](crate::ContentDigest::new), an empty field is not
+            /// encoded, and inserting it does not remove an existing field.
+            pub fn new() -> Self {
+                Self::default()
+            }
+
+            /// Sets the preference for algorithm `A`.
+            ///
+            /// # Panics
+            ///
+            /// Panics if `weight` exceeds [`MAX_WEIGHT`].
+            pub fn insert<A: Algorithm>(&mut self, weight: u8) {
+                assert!(weight <= MAX_WEIGHT, "weights range from 0 to {MAX_WEIGHT}");
+                insert_preference(&mut self.preferences, A::NAME.to_owned(), weight);
+            }
+
+            /// Sets the preference for algorithm `A`.
+            ///
+            /// # Panics
+            ///
+            /// Panics if `weight` exceeds [`MAX_WEIGHT`].
+            pub fn with<A: Algorithm>(mut self, weight: u8) -> Self {
+                self.insert::<A>(weight);
+                self
+            }
+
+            /// Returns the weight given to algorithm `A`, if it is mentioned.
+            pub fn weight<A: Algorithm>(&self) -> Option<u8> {
+                self.iter()
+                    .find(|(name, _)| *name == A::NAME)
+                    .map(|(_, weight)| weight)
+            }
+
+            /// Iterates over the algorithm tokens and their weights, in field order.
+            pub fn iter(&self) -> impl Iterator<Item = (&str, u8)> {
+                self.preferences
+                    .iter()
+                    .map(|(name, weight)| (name.as_str(), *weight))
+            }
+
+            /// Picks the algorithm a server should emit.
+            ///
+            /// `supported` lists the algorithm tokens the server is willing to compute, most
+            /// preferred first. The supported algorithm with the highest non-zero weight is
+            /// returned, with ties going to the earlier entry of `supported`. Returns `None` if
+            /// the client wants none of them, in which case the server may still send a digest
+            /// of its choosing.
+            pub fn negotiate<'a>(&self, supported: &[&'a str]) -> Option<&'a str> {
+                negotiate(&self.preferences, supported)
+            }
+        }
+
+        impl Header for $field {
+            fn name() -> &'static HeaderName {
+                &$name
+            }
+
+            fn decode<'i, I: Iterator<Item = &'i HeaderValue>>(
+                values: &mut I,
+            ) -> Result<Self, headers::Error> {
+                let mut field = Self::new();
+                for value in values {
+                    let value = value.to_str().map_err(|_| DigestError::NotAscii)?;
+                    parse_preferences(value, &mut field.preferences)?;
+                }
+                if field.preferences.is_empty() {
+                    return Err(DigestError::Missing.into());
+                }
+                Ok(field)
+            }
+
+            fn encode<E: Extend<HeaderValue>>(&self, values: &mut E) {
+                if self.preferences.is_empty() {
+                    return;
+                }
+                let value = HeaderValue::from_str(&self.to_string())
+                    .expect("serialized dictionaries are valid header values");
+                values.extend(std::iter::once(value));
+            }
+        }
+
+        /// Formats the field as a structured-field dictionary.
+        impl fmt::Display for $field {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                for (i, (name, weight)) in self.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{name}={weight}")?;
+                }
+                Ok(())
+            }
+        }
+
+        /// Parses the field from a structured-field dictionary.
+        impl FromStr for $field {
+            type Err = DigestError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                let mut field = Self::new();
+                parse_preferences(s, &mut field.preferences)?;
+                if field.preferences.is_empty() {
+                    return Err(DigestError::Missing);
+                }
+                Ok(field)
+            }
+        }
+    };
+}
+
+want_field! {
+    /// `Want-Content-Digest` header, defined in
+    /// [RFC 9530](https://datatracker.ietf.org/doc/html/rfc9530#section-4), asking for a
+    /// [`ContentDigest`](crate::ContentDigest) of the response.
+    ///
+    /// ## Example values
+    ///
+    /// * `sha-256=10, md5=3, sha=0`
+    ///
+    /// Weights range from `0` (not acceptable) to `10` (most preferred).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use headers::HeaderMapExt;
+    /// use http::{HeaderMap, HeaderValue};
+    /// use headers_content_md5::algorithm::{Algorithm, Md5, Sha512};
+    /// use headers_content_md5::{ContentDigest, ContentMd5, WantContentDigest};
+    ///
+    /// let mut request = HeaderMap::new();
+    /// request.insert(
+    ///     "want-content-digest",
+    ///     HeaderValue::from_static("sha-256=10, md5=3"),
+    /// );
+    ///
+    /// // This server only computes SHA-512 and MD5.
+    /// let want = request.typed_get::<WantContentDigest>().unwrap();
+    /// let chosen = want.negotiate(&[Sha512::NAME, Md5::NAME]);
+    /// assert_eq!(chosen, Some(Md5::NAME));
+    ///
+    /// let mut response = HeaderMap::new();
+    /// if chosen == Some(Md5::NAME) {
+    ///     let md5: ContentMd5 = "1B2M2Y8AsgTpgAmY7PhCfg==".parse().unwrap();
+    ///     response.typed_insert(ContentDigest::from(md5));
+    /// }
+    /// assert_eq!(response["content-digest"], "md5=:1B2M2Y8AsgTpgAmY7PhCfg==:");
+    /// ```
+    WantContentDigest, WANT_CONTENT_DIGEST
+}
+
+want_field! {
+    /// `Want-Repr-Digest` header, defined in
+    /// [RFC 9530](https://datatracker.ietf.org/doc/html/rfc9530#section-4), asking for a
+    /// [`ReprDigest`](crate::ReprDigest) of the response.
+    ///
+    /// Parsed and negotiated like [`WantContentDigest`].
+    WantReprDigest, WANT_REPR_DIGEST
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::algorithm::{Algorithm, Md5, Sha1, Sha256, Sha512};
+    use crate::{DigestError, WantContentDigest, WantReprDigest};
+    use headers::Header;
+    use http::HeaderValue;
+
+    #[test]
+    fn round_trips_preferences() {
+        let value = HeaderValue::from_static("sha-256=10, md5=3, sha=0");
+        let want = WantReprDigest::decode(&mut [&value].into_iter()).unwrap();
+        assert_eq!(want.weight::<Sha256>(), Some(10));
+        assert_eq!(want.weight::<Md5>(), Some(3));
+        assert_eq!(want.weight::<Sha512>(), None);
+
+        let mut values = Vec::new();
+        want.encode(&mut values);
+        assert_eq!(values, [value]);
+
+        let mut values = Vec::new();
+        WantReprDigest::new().encode(&mut values);
+        assert!(values.is_empty());
+        assert_eq!(
+            "md5=11".parse::<WantReprDigest>(),
+            Err(DigestError::InvalidDictionary)
+        );
+        assert!("md5=:AQ==:".parse::<WantReprDigest>().is_err());
+        assert_eq!("".parse::<WantReprDigest>(), Err(DigestError::Missing));
+        let empty = HeaderValue::from_static("");
+        assert!(WantReprDigest::decode(&mut [&empty].into_iter()).is_err());
+    }
+
+    #[test]
+    fn negotiates_algorithm() {
+        let want = WantContentDigest::new()
+            .with::<Sha256>(5)
+            .with::<Md5>(5)
+            .with::<Sha1>(0);
+
+        let cases: [(&[&str], _); 5] = [
+            (&[Sha256::NAME, Md5::NAME], Some(Sha256::NAME)),
+            (&[Md5::NAME, Sha256::NAME], Some(Md5::NAME)),
+            (&[Sha1::NAME, Md5::NAME], Some(Md5::NAME)),
+            (&[Sha1::NAME, Sha512::NAME], None),
+            (&[], None),
+        ];
+        for (supported, expected) in cases {
+            assert_eq!(want.negotiate(supported), expected, "{supported:?}");
+        }
+    }
+}
@@ -0,0 +1,235 @@
+//! Policies for requests carrying more than one value of a digest header.
+//!
+//! [`DigestHeader`] itself decodes with [`MultipleValues::RequireAgreement`]. To apply a
+//! different policy through [`Header::decode`], for example via
+//! [`HeaderMapExt::typed_get`](headers::HeaderMapExt::typed_get), decode a
+//! [`DigestHeaderWith`] parameterized with one of the marker types in this module.
+//!
+//! # Example
+//!
+//! ```
+//! use headers::HeaderMapExt;
+//! use http::{HeaderMap, HeaderValue};
+//! use headers_content_md5::{policy, ContentMd5, ContentMd5With};
+//!
+//! let mut headers = HeaderMap::new();
+//! headers.append("content-md5", HeaderValue::from_static("Q2hlY2sgSW50ZWdyaXR5IQ=="));
+//! headers.append("content-md5", HeaderValue::from_static("1B2M2Y8AsgTpgAmY7PhCfg=="));
+//!
+//! assert!(headers.typed_get::<ContentMd5>().is_none());
+//! assert!(headers.typed_get::<ContentMd5With<policy::Reject>>().is_none());
+//!
+//! let last = headers.typed_get::<ContentMd5With<policy::Last>>().unwrap();
+//! let expected = HeaderValue::from_static("1B2M2Y8AsgTpgAmY7PhCfg==");
+//! assert_eq!(last.into_inner(), ContentMd5::parse(&expected).unwrap());
+//! ```
+
+use crate::algorithm::{Algorithm, Md5};
+use crate::{DigestError, DigestHeader};
+use headers::{Header, HeaderValue};
+use std::marker::PhantomData;
+
+/// How to reconcile multiple values of a digest header.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MultipleValues {
+    /// Rejects more than one value.
+    Reject,
+    /// Accepts multiple values only if they all decode to the same digest.
+    #[default]
+    RequireAgreement,
+    /// Uses the first value and ignores the rest.
+    First,
+    /// Uses the last value and ignores the rest.
+    Last,
+}
+
+impl<A: Algorithm> DigestHeader<A> {
+    /// Decodes all values of the header in a message, reconciling multiple values according to
+    /// `policy`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use http::HeaderValue;
+    /// use headers_content_md5::{ContentMd5, ContentMd5Error, MultipleValues};
+    ///
+    /// let value = HeaderValue::from_static("Q2hlY2sgSW50ZWdyaXR5IQ==");
+    /// let values = [&value, &value];
+    ///
+    /// assert!(ContentMd5::decode_with(values, MultipleValues::RequireAgreement).is_ok());
+    /// assert_eq!(
+    ///     ContentMd5::decode_with(values, MultipleValues::Reject),
+    ///     Err(ContentMd5Error::MultipleValues)
+    /// );
+    /// ```
+    pub fn decode_with<'i, I>(values: I, policy: MultipleValues) -> Result<Self, DigestError>
+    where
+        I: IntoIterator<Item = &'i HeaderValue>,
+    {
+        let mut values = values.into_iter();
+        let first = values.next().ok_or(DigestError::Missing)?;
+
+        match policy {
+            MultipleValues::Reject => match values.next() {
+                Some(_) => Err(DigestError::MultipleValues),
+                None => Self::parse(first),
+            },
+            MultipleValues::RequireAgreement => {
+                let digest = Self::parse(first)?;
+                for value in values {
+                    if Self::parse(value)? != digest {
+                        return Err(DigestError::ConflictingValues);
+                    }
+                }
+                Ok(digest)
+            }
+            MultipleValues::First => Self::parse(first),
+            MultipleValues::Last => Self::parse(values.last().unwrap_or(first)),
+        }
+    }
+}
+
+/// Selects the [`MultipleValues`] policy applied by [`DigestHeaderWith`].
+pub trait Policy {
+    /// The policy to apply.
+    const MULTIPLE_VALUES: MultipleValues;
+}
+
+/// Applies [`MultipleValues::Reject`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Reject;
+
+/// Applies [`MultipleValues::RequireAgreement`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct RequireAgreement;
+
+/// Applies [`MultipleValues::First`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct First;
+
+/// Applies [`MultipleValues::Last`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Last;
+
+impl Policy for Reject {
+    const MULTIPLE_VALUES: MultipleValues = MultipleValues::Reject;
+}
+
+impl Policy for RequireAgreement {
+    const MULTIPLE_VALUES: MultipleValues = MultipleValues::RequireAgreement;
+}
+
+impl Policy for First {
+    const MULTIPLE_VALUES: MultipleValues = MultipleValues::First;
+}
+
+impl Policy for Last {
+    const MULTIPLE_VALUES: MultipleValues = MultipleValues::Last;
+}
+
+/// A [`DigestHeader`] whose [`Header`] implementation reconciles multiple values according to
+/// the policy `P`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct DigestHeaderWith<A: Algorithm, P> {
+    digest: DigestHeader<A>,
+    policy: PhantomData<P>,
+}
+
+/// A [`ContentMd5`](type@crate::ContentMd5) whose [`Header`] implementation reconciles multiple
+/// values according to the policy `P`.
+pub type ContentMd5With<P> = DigestHeaderWith<Md5, P>;
+
+impl<A: Algorithm, P> DigestHeaderWith<A, P> {
+    /// Wraps `digest`.
+    pub fn new(digest: DigestHeader<A>) -> Self {
+        Self {
+            digest,
+            policy: PhantomData,
+        }
+    }
+
+    /// Returns the wrapped [`DigestHeader`].
+    pub fn into_inner(self) -> DigestHeader<A> {
+        self.digest
+    }
+}
+
+impl<A: Algorithm, P> From<DigestHeader<A>> for DigestHeaderWith<A, P> {
+    fn from(digest: DigestHeader<A>) -> Self {
+        Self::new(digest)
+    }
+}
+
+impl<A: Algorithm, P> From<DigestHeaderWith<A, P>> for DigestHeader<A> {
+    fn from(digest: DigestHeaderWith<A, P>) -> Self {
+        digest.into_inner()
+    }
+}
+
+impl<A: Algorithm, P: Policy> Header for DigestHeaderWith<A, P> {
+    fn name() -> &'static http::header::HeaderName {
+        A::header_name()
+    }
+
+    fn decode<'i, I: Iterator<Item = &'i HeaderValue>>(
+        values: &mut I,
+    ) -> Result<Self, headers::Error> {
+        Ok(Self::new(DigestHeader::decode_with(
+            values,
+            P::MULTIPLE_VALUES,
+        )?))
+    }
+
+    fn encode<E: Extend<HeaderValue>>(&self, values: &mut E) {
+        self.digest.encode(values);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ContentMd5, ContentMd5Error, MultipleValues};
+    use http::HeaderValue;
+
+    #[test]
+    fn reconciles_values() {
+        let a = HeaderValue::from_static("Q2hlY2sgSW50ZWdyaXR5IQ==");
+        let b = HeaderValue::from_static("1B2M2Y8AsgTpgAmY7PhCfg==");
+        let md5_a = ContentMd5::parse(&a).unwrap();
+        let md5_b = ContentMd5::parse(&b).unwrap();
+
+        let cases = [
+            (MultipleValues::Reject, Err(ContentMd5Error::MultipleValues)),
+            (
+                MultipleValues::RequireAgreement,
+                Err(ContentMd5Error::ConflictingValues),
+            ),
+            (MultipleValues::First, Ok(md5_a)),
+            (MultipleValues::Last, Ok(md5_b)),
+        ];
+        for (policy, expected) in cases {
+            assert_eq!(
+                ContentMd5::decode_with([&a, &b], policy),
+                expected,
+                "{policy:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn accepts_single_value() {
+        let a = HeaderValue::from_static("Q2hlY2sgSW50ZWdyaXR5IQ==");
+        for policy in [
+            MultipleValues::Reject,
+            MultipleValues::RequireAgreement,
+            MultipleValues::First,
+            MultipleValues::Last,
+        ] {
+            assert!(ContentMd5::decode_with([&a], policy).is_ok(), "{policy:?}");
+            assert_eq!(
+                ContentMd5::decode_with([], policy),
+                Err(ContentMd5Error::Missing),
+                "{policy:?}"
+            );
+        }
+    }
+}
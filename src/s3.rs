@@ -0,0 +1,233 @@
+//! Amazon S3-style entity tags derived from MD5 digests.
+
+use crate::etag::strong_tag;
+use crate::{ContentMd5, DigestError};
+use headers::ETag;
+use std::fmt;
+use std::str::FromStr;
+
+/// An Amazon S3-compatible `ETag`, derived from the MD5 digest of an object.
+///
+/// Objects uploaded in a single part are tagged with the hex MD5 digest of their content.
+/// Objects uploaded in `N` parts are tagged with `hex(md5(concat(part_md5s)))-N`, the hex MD5
+/// digest of the concatenated binary MD5 digests of all parts followed by the part count.
+///
+/// # Example
+///
+/// ```
+/// use headers::ETag;
+/// use headers_content_md5::{ContentMd5, S3Etag};
+///
+/// let content_md5: ContentMd5 = "1B2M2Y8AsgTpgAmY7PhCfg==".parse().unwrap();
+///
+/// let etag: S3Etag = "\"d41d8cd98f00b204e9800998ecf8427e\"".parse().unwrap();
+/// assert!(!etag.is_multipart());
+/// assert!(etag.matches(content_md5));
+///
+/// let etag: ETag = etag.into();
+/// assert_eq!(S3Etag::try_from(&etag).unwrap().digest(), content_md5);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct S3Etag {
+    digest: ContentMd5,
+    parts: Option<u32>,
+}
+
+impl S3Etag {
+    /// Creates the `ETag` of an object uploaded in a single part.
+    pub const fn single(content_md5: ContentMd5) -> Self {
+        Self {
+            digest: content_md5,
+            parts: None,
+        }
+    }
+
+    /// Creates the `ETag` of an object uploaded in multiple parts, given the MD5 digest of each
+    /// part in upload order.
+    ///
+    /// Returns `None` if there are no parts, or more than [`u32::MAX`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use headers_content_md5::{ContentMd5, S3Etag};
+    ///
+    /// let parts = [ContentMd5::compute(b"Check "), ContentMd5::compute(b"Integrity!")];
+    /// let etag = S3Etag::multipart(parts).unwrap();
+    /// assert_eq!(etag.parts(), Some(2));
+    /// assert!(etag.verify_parts(parts));
+    /// assert!(!etag.matches(ContentMd5::compute(b"Check Integrity!")));
+    /// ```
+    #[cfg(feature = "md5")]
+    pub fn multipart(parts: impl IntoIterator<Item = ContentMd5>) -> Option<Self> {
+        let mut hasher = crate::ContentMd5Hasher::new();
+        let mut count = 0u32;
+        for part in parts {
+            hasher.update(part);
+            count = count.checked_add(1)?;
+        }
+        if count == 0 {
+            return None;
+        }
+        Some(Self {
+            digest: hasher.finalize(),
+            parts: Some(count),
+        })
+    }
+
+    /// Returns the digest carried by the tag.
+    ///
+    /// For multipart uploads this is the digest of the part digests, not of the object.
+    pub fn digest(&self) -> ContentMd5 {
+        self.digest
+    }
+
+    /// Returns the number of parts of a multipart upload, or `None` for a single-part upload.
+    pub fn parts(&self) -> Option<u32> {
+        self.parts
+    }
+
+    /// Returns `true` if the tag belongs to a multipart upload.
+    pub fn is_multipart(&self) -> bool {
+        self.parts.is_some()
+    }
+
+    /// Returns `true` if the tag belongs to a single-part upload of an object with the
+    /// `content_md5` digest, compared in constant time.
+    ///
+    /// The tags of multipart uploads never match, since they do not carry the digest of the
+    /// object; use `verify_parts` instead.
+    pub fn matches(&self, content_md5: ContentMd5) -> bool {
+        !self.is_multipart() && self.digest.ct_eq(&content_md5)
+    }
+
+    /// Returns `true` if the tag belongs to a multipart upload of parts with the given
+    /// digests, in upload order.
+    #[cfg(feature = "md5")]
+    pub fn verify_parts(&self, parts: impl IntoIterator<Item = ContentMd5>) -> bool {
+        match Self::multipart(parts) {
+            Some(expected) => expected.parts == self.parts && expected.digest.ct_eq(&self.digest),
+            None => false,
+        }
+    }
+}
+
+impl From<ContentMd5> for S3Etag {
+    fn from(content_md5: ContentMd5) -> Self {
+        Self::single(content_md5)
+    }
+}
+
+impl From<S3Etag> for ETag {
+    fn from(etag: S3Etag) -> Self {
+        format!("\"{etag}\"")
+            .parse()
+            .expect("hex digests are valid entity tags")
+    }
+}
+
+impl TryFrom<&ETag> for S3Etag {
+    type Error = DigestError;
+
+    /// Parses a strong entity tag; weak tags are rejected.
+    fn try_from(etag: &ETag) -> Result<Self, Self::Error> {
+        strong_tag(etag).ok_or(DigestError::InvalidEtag)?.parse()
+    }
+}
+
+/// Formats the tag without surrounding quotes, e.g. `d41d8cd98f00b204e9800998ecf8427e-2`.
+impl fmt::Display for S3Etag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.digest.to_hex())?;
+        match self.parts {
+            Some(parts) => write!(f, "-{parts}"),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Parses a tag with or without surrounding quotes.
+impl FromStr for S3Etag {
+    type Err = DigestError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s
+            .strip_prefix('"')
+            .and_then(|s| s.strip_suffix('"'))
+            .unwrap_or(s);
+        let (hex, parts) = match s.split_once('-') {
+            Some((hex, parts)) => {
+                // Only accept canonical, positive part counts.
+                if parts.starts_with('0') || !parts.bytes().all(|b| b.is_ascii_digit()) {
+                    return Err(DigestError::InvalidEtag);
+                }
+                let parts = parts.parse().map_err(|_| DigestError::InvalidEtag)?;
+                (hex, Some(parts))
+            }
+            None => (s, None),
+        };
+        Ok(Self {
+            digest: ContentMd5::from_hex(hex)?,
+            parts,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ContentMd5, DigestError, S3Etag};
+    use headers::ETag;
+
+    const EMPTY: &str = "d41d8cd98f00b204e9800998ecf8427e";
+
+    #[test]
+    fn round_trips_tags() {
+        let single: S3Etag = EMPTY.parse().unwrap();
+        assert_eq!(single, S3Etag::from(ContentMd5::from_hex(EMPTY).unwrap()));
+        assert_eq!(single.to_string(), EMPTY);
+
+        let multipart: S3Etag = format!("\"{EMPTY}-12\"").parse().unwrap();
+        assert_eq!(multipart.parts(), Some(12));
+        let etag = ETag::from(multipart);
+        assert_eq!(etag, format!("\"{EMPTY}-12\"").parse::<ETag>().unwrap());
+        assert_eq!(S3Etag::try_from(&etag), Ok(multipart));
+
+        let weak: ETag = format!("W/\"{EMPTY}\"").parse().unwrap();
+        assert_eq!(S3Etag::try_from(&weak), Err(DigestError::InvalidEtag));
+        for value in [
+            "",
+            "d41d8cd9",
+            &format!("{EMPTY}-0"),
+            &format!("{EMPTY}-01"),
+            &format!("{EMPTY}-+1"),
+            &format!("{EMPTY}-"),
+        ] {
+            assert!(value.parse::<S3Etag>().is_err(), "{value:?}");
+        }
+    }
+
+    #[cfg(feature = "md5")]
+    #[test]
+    fn computes_multipart_tags() {
+        let parts = [ContentMd5::compute(b"a"), ContentMd5::compute(b"b")];
+        let mut concatenated = Vec::new();
+        for part in parts {
+            concatenated.extend_from_slice(part.as_ref());
+        }
+
+        let etag = S3Etag::multipart(parts).unwrap();
+        assert_eq!(etag.digest(), ContentMd5::compute(&concatenated));
+        assert_eq!(
+            etag.to_string(),
+            format!("{}-2", ContentMd5::compute(&concatenated).to_hex())
+        );
+        assert!(etag.verify_parts(parts));
+        assert!(!etag.verify_parts([parts[0]]));
+        assert!(!etag.verify_parts([parts[1], parts[0]]));
+        assert_eq!(S3Etag::multipart([]), None);
+
+        let single = S3Etag::single(ContentMd5::compute(b"ab"));
+        assert!(single.matches(ContentMd5::compute(b"ab")));
+        assert!(!single.verify_parts(parts));
+    }
+}
@@ -0,0 +1,193 @@
+//! [`serde`](https://docs.rs/serde) support, available with the `serde` feature.
+//!
+//! [`DigestHeader`] serializes as its base64 header form by default. The [`hex`] and [`bytes`]
+//! modules can be used with `#[serde(with = ...)]` to select a different representation.
+//! Deserialization applies the same validation as decoding the header.
+//!
+//! # Example
+//!
+//! ```
+//! use headers_content_md5::ContentMd5;
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Manifest {
+//!     md5: ContentMd5,
+//!     #[serde(with = "headers_content_md5::serde::hex")]
+//!     md5_hex: ContentMd5,
+//! }
+//!
+//! let md5 = ContentMd5(*b"Check Integrity!");
+//! let json = serde_json::to_string(&Manifest { md5, md5_hex: md5 }).unwrap();
+//! assert_eq!(
+//!     json,
+//!     r#"{"md5":"Q2hlY2sgSW50ZWdyaXR5IQ==","md5_hex":"436865636b20496e7465677269747921"}"#
+//! );
+//! ```
+
+use crate::algorithm::Algorithm;
+use crate::DigestHeader;
+use ::serde::de::{self, Deserialize, Deserializer, Visitor};
+use ::serde::ser::{Serialize, Serializer};
+use std::fmt;
+use std::marker::PhantomData;
+
+impl<A: Algorithm> Serialize for DigestHeader<A> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de, A: Algorithm> Deserialize<'de> for DigestHeader<A> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_str(Base64Visitor(PhantomData))
+    }
+}
+
+struct Base64Visitor<A>(PhantomData<A>);
+
+impl<A: Algorithm> Visitor<'_> for Base64Visitor<A> {
+    type Value = DigestHeader<A>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "a base64-encoded {} digest", A::NAME)
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        DigestHeader::from_base64(v).map_err(E::custom)
+    }
+}
+
+/// Serializes a [`DigestHeader`] as lowercase hex characters.
+pub mod hex {
+    use crate::algorithm::Algorithm;
+    use crate::DigestHeader;
+    use ::serde::de::{self, Deserializer, Visitor};
+    use ::serde::ser::Serializer;
+    use std::fmt;
+    use std::marker::PhantomData;
+
+    /// Serializes `digest` as a hex string.
+    pub fn serialize<A: Algorithm, S: Serializer>(
+        digest: &DigestHeader<A>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&digest.to_hex())
+    }
+
+    /// Deserializes a [`DigestHeader`] from a hex string.
+    pub fn deserialize<'de, A: Algorithm, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<DigestHeader<A>, D::Error> {
+        deserializer.deserialize_str(HexVisitor(PhantomData))
+    }
+
+    struct HexVisitor<A>(PhantomData<A>);
+
+    impl<A: Algorithm> Visitor<'_> for HexVisitor<A> {
+        type Value = DigestHeader<A>;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "a hex-encoded {} digest", A::NAME)
+        }
+
+        fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+            DigestHeader::from_hex(v).map_err(E::custom)
+        }
+    }
+}
+
+/// Serializes a [`DigestHeader`] as its raw bytes.
+pub mod bytes {
+    use crate::algorithm::{Algorithm, MAX_DIGEST_LEN};
+    use crate::DigestHeader;
+    use ::serde::de::{self, Deserializer, SeqAccess, Visitor};
+    use ::serde::ser::Serializer;
+    use std::fmt;
+    use std::marker::PhantomData;
+
+    /// Serializes `digest` as a byte array.
+    pub fn serialize<A: Algorithm, S: Serializer>(
+        digest: &DigestHeader<A>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(digest.as_ref())
+    }
+
+    /// Deserializes a [`DigestHeader`] from a byte array or a sequence of bytes.
+    pub fn deserialize<'de, A: Algorithm, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<DigestHeader<A>, D::Error> {
+        deserializer.deserialize_bytes(BytesVisitor(PhantomData))
+    }
+
+    struct BytesVisitor<A>(PhantomData<A>);
+
+    impl<'de, A: Algorithm> Visitor<'de> for BytesVisitor<A> {
+        type Value = DigestHeader<A>;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{} bytes of {} digest", A::DIGEST_LEN, A::NAME)
+        }
+
+        fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+            DigestHeader::try_from(v).map_err(E::custom)
+        }
+
+        fn visit_seq<S: SeqAccess<'de>>(self, mut seq: S) -> Result<Self::Value, S::Error> {
+            let mut digest = [0; MAX_DIGEST_LEN];
+            for (i, byte) in digest[..A::DIGEST_LEN].iter_mut().enumerate() {
+                *byte = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(i, &self))?;
+            }
+            if seq.next_element::<u8>()?.is_some() {
+                return Err(de::Error::invalid_length(A::DIGEST_LEN + 1, &self));
+            }
+            DigestHeader::try_from(&digest[..A::DIGEST_LEN]).map_err(de::Error::custom)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ContentMd5;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Manifest {
+        md5: ContentMd5,
+        #[serde(with = "crate::serde::hex")]
+        hex: ContentMd5,
+        #[serde(with = "crate::serde::bytes")]
+        bytes: ContentMd5,
+    }
+
+    #[test]
+    fn round_trips_representations() {
+        let md5 = ContentMd5(*b"Check Integrity!");
+        let manifest = Manifest {
+            md5,
+            hex: md5,
+            bytes: md5,
+        };
+
+        let json = serde_json::to_string(&manifest).unwrap();
+        assert_eq!(
+            json,
+            r#"{"md5":"Q2hlY2sgSW50ZWdyaXR5IQ==","hex":"436865636b20496e7465677269747921","bytes":[67,104,101,99,107,32,73,110,116,101,103,114,105,116,121,33]}"#
+        );
+        assert_eq!(serde_json::from_str::<Manifest>(&json).unwrap(), manifest);
+    }
+
+    #[test]
+    fn validates_like_header_decoding() {
+        for json in [
+            r#""Q2hlY2sgSW50ZWdyaXR5IQ""#,
+            r#""Q2hlY2sgSW50ZWdyaXR5IT8/""#,
+            r#""436865636b20496e7465677269747921""#,
+        ] {
+            assert!(serde_json::from_str::<ContentMd5>(json).is_err(), "{json}");
+        }
+    }
+}
@@ -0,0 +1,251 @@
+//! A minimal parser and serializer for [RFC 8941](https://datatracker.ietf.org/doc/html/rfc8941)
+//! structured-field dictionaries, as used by the RFC 9530 digest fields.
+//!
+//! Member parameters are parsed for validity but discarded, since none of the digest fields
+//! define any.
+
+use base64::alphabet;
+use base64::engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig};
+use base64::Engine;
+
+/// Byte sequences are standard base64; RFC 8941 asks parsers to tolerate missing padding.
+const BYTE_SEQUENCE: GeneralPurpose = GeneralPurpose::new(
+    &alphabet::STANDARD,
+    GeneralPurposeConfig::new()
+        .with_decode_padding_mode(DecodePaddingMode::Indifferent)
+        .with_decode_allow_trailing_bits(true),
+);
+
+/// A bare item of a structured field.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum BareItem {
+    Integer(i64),
+    Decimal(f64),
+    String(String),
+    Token(String),
+    ByteSequence(Vec<u8>),
+    Boolean(bool),
+}
+
+/// Parses a dictionary, returning its members in order with later duplicates replacing
+/// earlier ones. Inner lists are rejected.
+pub(crate) fn parse_dictionary(input: &str) -> Option<Vec<(String, BareItem)>> {
+    let mut parser = Parser {
+        input: input.as_bytes(),
+        pos: 0,
+    };
+    let mut members: Vec<(String, BareItem)> = Vec::new();
+
+    parser.skip_sp();
+    while !parser.is_done() {
+        let key = parser.key()?;
+        let item = if parser.eat(b'=') {
+            parser.bare_item()?
+        } else {
+            BareItem::Boolean(true)
+        };
+        parser.parameters()?;
+
+        match members.iter_mut().find(|(existing, _)| *existing == key) {
+            Some(member) => member.1 = item,
+            None => members.push((key, item)),
+        }
+
+        parser.skip_ows();
+        if parser.is_done() {
+            break;
+        }
+        if !parser.eat(b',') {
+            return None;
+        }
+        parser.skip_ows();
+        if parser.is_done() {
+            // A trailing comma is not allowed.
+            return None;
+        }
+    }
+    Some(members)
+}
+
+/// Serializes a byte sequence member, e.g. `sha-256=:AAAA:`.
+pub(crate) fn serialize_byte_sequence(key: &str, bytes: &[u8]) -> String {
+    format!("{key}=:{}:", BYTE_SEQUENCE.encode(bytes))
+}
+
+struct Parser<'a> {
+    input: &'a [u8],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn is_done(&self) -> bool {
+        self.pos >= self.input.len()
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.input.get(self.pos).copied()
+    }
+
+    fn eat(&mut self, byte: u8) -> bool {
+        let matches = self.peek() == Some(byte);
+        if matches {
+            self.pos += 1;
+        }
+        matches
+    }
+
+    fn skip_sp(&mut self) {
+        while self.eat(b' ') {}
+    }
+
+    fn skip_ows(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t')) {
+            self.pos += 1;
+        }
+    }
+
+    fn take_while(&mut self, predicate: impl Fn(u8) -> bool) -> &str {
+        let start = self.pos;
+        while self.peek().is_some_and(&predicate) {
+            self.pos += 1;
+        }
+        // Only ASCII bytes are ever accepted by the predicates used below.
+        std::str::from_utf8(&self.input[start..self.pos]).unwrap_or_default()
+    }
+
+    fn key(&mut self) -> Option<String> {
+        if !matches!(self.peek(), Some(b'a'..=b'z' | b'*')) {
+            return None;
+        }
+        let key = self.take_while(|b| {
+            b.is_ascii_lowercase() || b.is_ascii_digit() || matches!(b, b'_' | b'-' | b'.' | b'*')
+        });
+        Some(key.to_owned())
+    }
+
+    fn parameters(&mut self) -> Option<()> {
+        while self.eat(b';') {
+            self.skip_sp();
+            self.key()?;
+            if self.eat(b'=') {
+                self.bare_item()?;
+            }
+        }
+        Some(())
+    }
+
+    fn bare_item(&mut self) -> Option<BareItem> {
+        match self.peek()? {
+            b'-' | b'0'..=b'9' => self.number(),
+            b'"' => self.string(),
+            b':' => self.byte_sequence(),
+            b'?' => self.boolean(),
+            b'A'..=b'Z' | b'a'..=b'z' | b'*' => {
+                let token = self
+                    .take_while(|b| b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~:/".contains(&b));
+                Some(BareItem::Token(token.to_owned()))
+            }
+            _ => None,
+        }
+    }
+
+    fn number(&mut self) -> Option<BareItem> {
+        let negative = self.eat(b'-');
+        let integer = self.take_while(|b| b.is_ascii_digit()).to_owned();
+        if integer.is_empty() || integer.len() > 15 {
+            return None;
+        }
+        let sign = if negative { -1 } else { 1 };
+
+        if !self.eat(b'.') {
+            return Some(BareItem::Integer(sign * integer.parse::<i64>().ok()?));
+        }
+        let fraction = self.take_while(|b| b.is_ascii_digit());
+        if integer.len() > 12 || fraction.is_empty() || fraction.len() > 3 {
+            return None;
+        }
+        let value: f64 = format!("{integer}.{fraction}").parse().ok()?;
+        Some(BareItem::Decimal(sign as f64 * value))
+    }
+
+    fn string(&mut self) -> Option<BareItem> {
+        self.pos += 1;
+        let mut value = String::new();
+        loop {
+            match self.peek()? {
+                b'"' => {
+                    self.pos += 1;
+                    return Some(BareItem::String(value));
+                }
+                b'\\' => {
+                    self.pos += 1;
+                    let escaped = self.peek().filter(|b| matches!(b, b'"' | b'\\'))?;
+                    value.push(char::from(escaped));
+                }
+                byte @ 0x20..=0x7e => value.push(char::from(byte)),
+                _ => return None,
+            }
+            self.pos += 1;
+        }
+    }
+
+    fn byte_sequence(&mut self) -> Option<BareItem> {
+        self.pos += 1;
+        let encoded = self
+            .take_while(|b| b.is_ascii_alphanumeric() || matches!(b, b'+' | b'/' | b'='))
+            .to_owned();
+        if !self.eat(b':') {
+            return None;
+        }
+        let bytes = BYTE_SEQUENCE.decode(encoded).ok()?;
+        Some(BareItem::ByteSequence(bytes))
+    }
+
+    fn boolean(&mut self) -> Option<BareItem> {
+        self.pos += 1;
+        if self.eat(b'1') {
+            Some(BareItem::Boolean(true))
+        } else if self.eat(b'0') {
+            Some(BareItem::Boolean(false))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::structured::{parse_dictionary, serialize_byte_sequence, BareItem};
+
+    #[test]
+    fn parses_members() {
+        let members = parse_dictionary(
+            "sha-256=:AAEC:, md5=:AQ==:;x=1,\tq=5, k=?0, t=tok, s=\"a\\\"b\", d=0.5",
+        )
+        .unwrap();
+        assert_eq!(
+            members,
+            [
+                ("sha-256".into(), BareItem::ByteSequence(vec![0, 1, 2])),
+                ("md5".into(), BareItem::ByteSequence(vec![1])),
+                ("q".into(), BareItem::Integer(5)),
+                ("k".into(), BareItem::Boolean(false)),
+                ("t".into(), BareItem::Token("tok".into())),
+                ("s".into(), BareItem::String("a\"b".into())),
+                ("d".into(), BareItem::Decimal(0.5)),
+            ]
+        );
+        assert_eq!(
+            parse_dictionary("a=1, a=2").unwrap(),
+            [("a".into(), BareItem::Integer(2))]
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_input() {
+        for input in ["a=1,", "A=1", "a=:AA", "a=(1 2)", "a=1 b=2", "a=\"x"] {
+            assert!(parse_dictionary(input).is_none(), "{input:?}");
+        }
+        assert_eq!(serialize_byte_sequence("md5", &[1]), "md5=:AQ==:");
+    }
+}
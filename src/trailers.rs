@@ -0,0 +1,253 @@
+//! Reading digests from HTTP trailers, and deciding when to send them as trailers.
+
+use crate::algorithm::Algorithm;
+use crate::{DigestError, DigestHeader, MultipleValues};
+use http::header::{CONTENT_LENGTH, TE, TRAILER, TRANSFER_ENCODING};
+use http::{HeaderMap, HeaderName, Request, Response, Version};
+
+/// Where the digest of a response body is sent.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DigestPlacement {
+    /// In the header block, which requires buffering the body to compute the digest first.
+    Header,
+    /// As a trailer after the streamed body, announced in the `Trailer` header.
+    Trailer,
+}
+
+/// Returns `true` if `request` allows trailers in its response, i.e. it was sent over HTTP/1.1
+/// or later with `TE: trailers`.
+///
+/// # Example
+///
+/// ```
+/// use http::{Request, Version};
+/// use headers_content_md5::accepts_trailers;
+///
+/// let request = Request::get("/").header("te", "trailers").body(()).unwrap();
+/// assert!(accepts_trailers(&request));
+///
+/// let request = Request::get("/").version(Version::HTTP_10).body(()).unwrap();
+/// assert!(!accepts_trailers(&request));
+/// ```
+pub fn accepts_trailers<B>(request: &Request<B>) -> bool {
+    request.version() >= Version::HTTP_11
+        && list_contains(request.headers(), &TE, |token| {
+            // Strip any transfer-coding parameters, e.g. `trailers;q=1`.
+            let token = token.split(';').next().unwrap_or_default();
+            token.trim().eq_ignore_ascii_case("trailers")
+        })
+}
+
+/// Decides where to place the digest of `response` and announces a trailer placement, given
+/// whether the peer accepts trailers and the HTTP version of the exchange.
+pub(crate) fn announce<A: Algorithm, B>(
+    response: &mut Response<B>,
+    accepts_trailers: bool,
+    version: Version,
+) -> DigestPlacement {
+    // HTTP/1.1 can only carry trailers in a chunked message.
+    let chunked = version != Version::HTTP_11
+        || (!response.headers().contains_key(CONTENT_LENGTH)
+            && response
+                .headers()
+                .get_all(TRANSFER_ENCODING)
+                .iter()
+                .filter_map(|value| value.to_str().ok())
+                .flat_map(|value| value.split(','))
+                .last()
+                .is_none_or(|coding| coding.trim().eq_ignore_ascii_case("chunked")));
+    if !accepts_trailers || !chunked {
+        return DigestPlacement::Header;
+    }
+
+    if !DigestHeader::<A>::is_announced(response.headers()) {
+        response
+            .headers_mut()
+            .append(TRAILER, A::header_name().clone().into());
+    }
+    DigestPlacement::Trailer
+}
+
+fn list_contains(headers: &HeaderMap, name: &HeaderName, matches: impl Fn(&str) -> bool) -> bool {
+    headers
+        .get_all(name)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(matches)
+}
+
+impl<A: Algorithm> DigestHeader<A> {
+    /// Decodes the digest from a trailer block, such as one obtained through
+    /// `http_body::Frame::into_trailers`.
+    ///
+    /// This applies the same validation as [`Header::decode`](headers::Header::decode).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use http::{HeaderMap, HeaderValue};
+    /// use headers_content_md5::{ContentMd5, DigestError};
+    ///
+    /// let mut trailers = HeaderMap::new();
+    /// assert_eq!(ContentMd5::from_trailers(&trailers), Err(DigestError::Missing));
+    ///
+    /// trailers.insert("content-md5", HeaderValue::from_static("Q2hlY2sgSW50ZWdyaXR5IQ=="));
+    /// assert_eq!(
+    ///     ContentMd5::from_trailers(&trailers).unwrap().0,
+    ///     "Check Integrity!".as_bytes()
+    /// );
+    /// ```
+    pub fn from_trailers(trailers: &HeaderMap) -> Result<Self, DigestError> {
+        Self::decode_with(
+            trailers.get_all(A::header_name()),
+            MultipleValues::RequireAgreement,
+        )
+    }
+
+    /// Returns `true` if the `Trailer` header in `headers` announces this digest as a trailer.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use http::{HeaderMap, HeaderValue};
+    /// use headers_content_md5::ContentMd5;
+    ///
+    /// let mut headers = HeaderMap::new();
+    /// assert!(!ContentMd5::is_announced(&headers));
+    ///
+    /// headers.insert("trailer", HeaderValue::from_static("Expires, Content-MD5"));
+    /// assert!(ContentMd5::is_announced(&headers));
+    /// ```
+    pub fn is_announced(headers: &HeaderMap) -> bool {
+        list_contains(headers, &TRAILER, |name| {
+            name.trim().eq_ignore_ascii_case(A::header_name().as_str())
+        })
+    }
+
+    /// Decides whether the digest of `response` may be sent as a trailer, and if so announces
+    /// it in the `Trailer` header of `response`.
+    ///
+    /// A trailer is permitted if `request` [accepts trailers](accepts_trailers) and, for
+    /// HTTP/1.1, `response` will be sent with chunked encoding, i.e. it has no
+    /// `Content-Length` and any `Transfer-Encoding` ends in `chunked`. Otherwise
+    /// [`DigestPlacement::Header`] is returned and the body must be buffered to place the
+    /// digest in the header block. `SetDigestLayer` does so up to its buffer limit, and sends
+    /// larger bodies without a digest.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use http::{Request, Response};
+    /// use headers_content_md5::{ContentMd5, DigestPlacement};
+    ///
+    /// let request = Request::get("/").header("te", "trailers").body(()).unwrap();
+    /// let mut response = Response::new(());
+    /// assert_eq!(
+    ///     ContentMd5::announce_trailer(&request, &mut response),
+    ///     DigestPlacement::Trailer
+    /// );
+    /// assert_eq!(response.headers()["trailer"], "content-md5");
+    ///
+    /// let mut response = Response::builder().header("content-length", "16").body(()).unwrap();
+    /// assert_eq!(
+    ///     ContentMd5::announce_trailer(&request, &mut response),
+    ///     DigestPlacement::Header
+    /// );
+    /// assert!(!response.headers().contains_key("trailer"));
+    /// ```
+    pub fn announce_trailer<ReqBody, ResBody>(
+        request: &Request<ReqBody>,
+        response: &mut Response<ResBody>,
+    ) -> DigestPlacement {
+        announce::<A, _>(response, accepts_trailers(request), request.version())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::algorithm::Sha256;
+    use crate::{ContentMd5, DigestError, DigestHeader, DigestPlacement};
+    use http::{HeaderMap, HeaderValue, Request, Response, Version};
+
+    #[test]
+    fn decodes_trailers() {
+        let mut trailers = HeaderMap::new();
+        trailers.append(
+            "content-md5",
+            HeaderValue::from_static("1B2M2Y8AsgTpgAmY7PhCfg=="),
+        );
+        assert!(ContentMd5::from_trailers(&trailers).is_ok());
+        assert_eq!(
+            DigestHeader::<Sha256>::from_trailers(&trailers),
+            Err(DigestError::Missing)
+        );
+
+        trailers.append(
+            "content-md5",
+            HeaderValue::from_static("Q2hlY2sgSW50ZWdyaXR5IQ=="),
+        );
+        assert_eq!(
+            ContentMd5::from_trailers(&trailers),
+            Err(DigestError::ConflictingValues)
+        );
+    }
+
+    #[test]
+    fn announces_trailer_when_permitted() {
+        let cases = [
+            (Version::HTTP_11, "trailers", None, DigestPlacement::Trailer),
+            (
+                Version::HTTP_11,
+                "gzip, trailers;q=1",
+                Some(("transfer-encoding", "gzip, chunked")),
+                DigestPlacement::Trailer,
+            ),
+            (
+                Version::HTTP_2,
+                "trailers",
+                Some(("content-length", "16")),
+                DigestPlacement::Trailer,
+            ),
+            (Version::HTTP_11, "gzip", None, DigestPlacement::Header),
+            (Version::HTTP_10, "trailers", None, DigestPlacement::Header),
+            (
+                Version::HTTP_11,
+                "trailers",
+                Some(("content-length", "16")),
+                DigestPlacement::Header,
+            ),
+        ];
+        for (version, te, header, placement) in cases {
+            let request = Request::get("/")
+                .version(version)
+                .header("te", te)
+                .body(())
+                .unwrap();
+            let mut response = Response::new(());
+            if let Some((name, value)) = header {
+                response
+                    .headers_mut()
+                    .insert(name, HeaderValue::from_static(value));
+            }
+            assert_eq!(
+                ContentMd5::announce_trailer(&request, &mut response),
+                placement,
+                "{version:?} {te} {header:?}"
+            );
+            assert_eq!(
+                ContentMd5::is_announced(response.headers()),
+                placement == DigestPlacement::Trailer
+            );
+        }
+    }
+
+    #[test]
+    fn detects_announcement() {
+        let mut headers = HeaderMap::new();
+        headers.append("trailer", HeaderValue::from_static("expires"));
+        headers.append("trailer", HeaderValue::from_static(" content-md5 ,x-other"));
+        assert!(ContentMd5::is_announced(&headers));
+        assert!(!DigestHeader::<Sha256>::is_announced(&headers));
+    }
+}
@@ -0,0 +1,106 @@
+//! Body verification against a [`DigestHeader`], available with the `md5`, `sha1` and `sha2`
+//! features.
+
+use crate::algorithm::{Algorithm, Compute, Md5};
+use crate::DigestHeader;
+use std::fmt;
+
+impl<A: Compute> DigestHeader<A> {
+    /// Verifies that `body` hashes to this digest.
+    ///
+    /// # Example
+    ///
+    #[cfg_attr(feature = "md5", doc = "```")]
+    #[cfg_attr(not(feature = "md5"), doc = "```ignore")]
+    /// use headers::Header;
+    /// use http::HeaderValue;
+    /// use headers_content_md5::ContentMd5;
+    ///
+    /// let value = HeaderValue::from_static("1B2M2Y8AsgTpgAmY7PhCfg==");
+    /// let md5 = ContentMd5::decode(&mut [&value].into_iter()).unwrap();
+    ///
+    /// assert!(md5.verify(b"").is_ok());
+    ///
+    /// let err = md5.verify(b"corrupted").unwrap_err();
+    /// assert_eq!(err.expected_base64(), "1B2M2Y8AsgTpgAmY7PhCfg==");
+    /// ```
+    pub fn verify(&self, body: &[u8]) -> Result<(), DigestMismatch<A>> {
+        DigestMismatch::check(*self, Self::compute(body))
+    }
+}
+
+/// The error returned when a body does not match its [`DigestHeader`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DigestMismatch<A: Algorithm> {
+    /// The digest announced by the header.
+    pub expected: DigestHeader<A>,
+    /// The digest computed from the body.
+    pub actual: DigestHeader<A>,
+}
+
+/// The error returned when a body does not match its [`ContentMd5`](type@crate::ContentMd5).
+pub type Md5Mismatch = DigestMismatch<Md5>;
+
+impl<A: Algorithm> DigestMismatch<A> {
+    /// Compares the `expected` digest against the `actual` one in constant time.
+    pub(crate) fn check(expected: DigestHeader<A>, actual: DigestHeader<A>) -> Result<(), Self> {
+        if expected.ct_eq(&actual) {
+            Ok(())
+        } else {
+            Err(Self { expected, actual })
+        }
+    }
+
+    /// Returns the expected digest in its base64 header form.
+    pub fn expected_base64(&self) -> String {
+        self.expected.to_base64()
+    }
+
+    /// Returns the actual digest in its base64 header form.
+    pub fn actual_base64(&self) -> String {
+        self.actual.to_base64()
+    }
+}
+
+impl<A: Algorithm> fmt::Display for DigestMismatch<A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} digest mismatch: expected {}, got {}",
+            A::NAME,
+            self.expected_base64(),
+            self.actual_base64()
+        )
+    }
+}
+
+impl<A: Algorithm> std::error::Error for DigestMismatch<A> {}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "md5")]
+    use crate::ContentMd5;
+
+    #[cfg(feature = "md5")]
+    #[test]
+    fn verify_works() {
+        let md5 = ContentMd5::compute(b"Check Integrity!");
+        assert!(md5.verify(b"Check Integrity!").is_ok());
+    }
+
+    #[cfg(feature = "md5")]
+    #[test]
+    fn verify_reports_mismatch() {
+        let md5 = ContentMd5::compute(b"");
+        let err = md5.verify(b"Check Integrity!").unwrap_err();
+        assert_eq!(err.expected, md5);
+        assert_eq!(err.actual, ContentMd5::compute(b"Check Integrity!"));
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "md5 digest mismatch: expected 1B2M2Y8AsgTpgAmY7PhCfg==, got {}",
+                err.actual_base64()
+            )
+        );
+    }
+}